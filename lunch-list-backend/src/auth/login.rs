@@ -38,8 +38,8 @@ where
     Ok(username.trim().to_string())
 }
 
-/// Returns true when the username is a valid username, false otherwise.
-fn validate_username(username: &str) -> Result<(), ValidationError> {
+/// Returns an error when the username contains invalid characters.
+pub fn validate_username(username: &str) -> Result<(), ValidationError> {
     let valid = username.chars().all(|c| c.is_alphanumeric() || c == ' ');
     if valid {
         Ok(())
@@ -67,13 +67,18 @@ impl Signup {
     }
 
     pub fn hash(&self) -> argon2::Result<String> {
-        let mut salt = [0u8; 32];
-        rand::thread_rng().fill(&mut salt[..]);
-        let config = argon2::Config::default();
-        argon2::hash_encoded(self.password.as_bytes(), &salt, &config)
+        hash_password(&self.password)
     }
 }
 
+/// Returns the encoded hash of a password with a random salt.
+pub fn hash_password(password: &str) -> argon2::Result<String> {
+    let mut salt = [0u8; 32];
+    rand::thread_rng().fill(&mut salt[..]);
+    let config = argon2::Config::default();
+    argon2::hash_encoded(password.as_bytes(), &salt, &config)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
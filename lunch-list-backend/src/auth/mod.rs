@@ -6,6 +6,7 @@ mod logout;
 mod routes;
 
 pub use claims::{Claims, ClaimsConfig};
+pub use login::{hash_password, validate_username};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -1,11 +1,8 @@
-use std::ops::DerefMut;
-
 use actix_web::{
     cookie::{Cookie, SameSite},
     get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder,
 };
 use digest::Digest;
-use mobc_redis::{redis, redis::AsyncCommands};
 use serde_json::json;
 use validator::Validate;
 
@@ -14,29 +11,30 @@ use super::{
     login::{Login, Signup},
     logout::LogoutRequest,
 };
-use crate::{errors::ServiceError, AppState, Pool};
+use crate::{errors::ServiceError, store::Store, AppState};
 
 type Hasher = blake2::Blake2b;
 
 #[post("/login")]
 pub async fn login(
     login: web::Json<Login>,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, ServiceError> {
     let login = login.into_inner();
-    let mut conn = db.get().await?;
-    let id: Option<usize> = conn.hget("users", &login.username).await?;
-    match id {
-        Some(id) => {
-            let password: String = conn.hget(&format!("user:{}", id), "password").await?;
-            if login.verify_hash(&password)? {
+    let id = store.user_id(&login.username).await?;
+    let user = match id {
+        Some(id) => store.user(id).await?,
+        None => None,
+    };
+    match user {
+        Some(user) => {
+            if login.verify_hash(&user.password)? {
                 let (access_token, refresh_token) =
-                    get_token_pair(id, login.username, state.token_secret.as_bytes())?;
+                    get_token_pair(user.id, login.username, state.token_secret.as_bytes())?;
                 let digest = Hasher::digest(refresh_token.as_bytes());
 
-                conn.sadd(&format!("refresh_tokens:{}", id), digest.as_slice())
-                    .await?;
+                store.add_refresh_token(user.id, &digest).await?;
 
                 let refresh_cookie = Cookie::build("refresh_token", refresh_token)
                     .http_only(true)
@@ -56,7 +54,7 @@ pub async fn login(
 #[get("/refresh")]
 pub async fn refresh(
     req: HttpRequest,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, ServiceError> {
     let token = req
@@ -65,26 +63,24 @@ pub async fn refresh(
         .ok_or(ServiceError::Unauthorized)?;
     let claims = decode::<RefreshClaims>(&token, state.token_secret.as_bytes())?;
 
-    let mut conn = db.get().await?;
     let digest = Hasher::digest(token.as_bytes());
-    let valid: bool = conn
-        .srem(&format!("refresh_tokens:{}", claims.sub), digest.as_slice())
-        .await?;
+    let valid = store.remove_refresh_token(claims.sub, &digest).await?;
     if !valid {
-        conn.del(&format!("refresh_tokens:{}", claims.sub)).await?;
+        store.clear_refresh_tokens(claims.sub).await?;
         return Err(ServiceError::InvalidRefreshToken);
     }
 
-    let name = conn
-        .hget(&format!("user:{}", claims.sub), "username")
-        .await?;
+    let name = store
+        .user(claims.sub)
+        .await?
+        .map(|user| user.username)
+        .ok_or(ServiceError::Unauthorized)?;
 
     let (access_token, refresh_token) =
         get_token_pair(claims.sub, name, state.token_secret.as_bytes())?;
 
     let digest = Hasher::digest(refresh_token.as_bytes());
-    conn.sadd(&format!("refresh_tokens:{}", claims.sub), digest.as_slice())
-        .await?;
+    store.add_refresh_token(claims.sub, &digest).await?;
 
     let refresh_cookie = Cookie::build("refresh_token", refresh_token)
         .http_only(true)
@@ -99,7 +95,7 @@ pub async fn refresh(
 pub async fn logout(
     req: HttpRequest,
     query: web::Query<LogoutRequest>,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, ServiceError> {
     let refresh_cookie = req
@@ -107,15 +103,12 @@ pub async fn logout(
         .ok_or(ServiceError::Unauthorized)?;
     let claims = decode::<RefreshClaims>(refresh_cookie.value(), state.token_secret.as_bytes())?;
 
-    let mut conn = db.get().await?;
-
     if query.all {
-        conn.del(&format!("refresh_tokens:{}", claims.sub)).await?;
+        store.clear_refresh_tokens(claims.sub).await?;
     } else {
         let token = refresh_cookie.value();
         let digest = Hasher::digest(token.as_bytes());
-        conn.srem(&format!("refresh_tokens:{}", claims.sub), digest.as_slice())
-            .await?;
+        store.remove_refresh_token(claims.sub, &digest).await?;
     }
 
     Ok(HttpResponse::NoContent()
@@ -126,7 +119,7 @@ pub async fn logout(
 #[post("/signup")]
 pub async fn signup(
     user: web::Json<Signup>,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, ServiceError> {
     let user = user.into_inner();
@@ -136,22 +129,6 @@ pub async fn signup(
         return Err(ServiceError::InvalidSignupSecret);
     }
 
-    let mut conn = db.get().await?;
-    let exists: bool = conn.hexists("users", &user.username).await?;
-    if exists {
-        Err(ServiceError::UserAlreadyExists {
-            username: user.username,
-        })
-    } else {
-        let user_id: usize = conn.incr("next_user_id", 1usize).await?;
-        redis::pipe()
-            .hset("users", &user.username, user_id)
-            .hset_multiple(
-                &format!("user:{}", user_id),
-                &[("username", &user.username), ("password", &user.hash()?)],
-            )
-            .query_async(conn.deref_mut())
-            .await?;
-        Ok(HttpResponse::Created().json(json!({ "id": user_id })))
-    }
+    let user_id = store.create_user(&user.username, &user.hash()?).await?;
+    Ok(HttpResponse::Created().json(json!({ "id": user_id })))
 }
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use digest::Digest;
+use rand::Rng;
+
+type Hasher = blake2::Blake2b;
+
+/// Number of bytes of the digest used in feed tokens.
+const TOKEN_BYTES: usize = 16;
+
+/// Number of random bytes in the feed secret of a user.
+const FEED_SECRET_BYTES: usize = 16;
+
+/// Longest content line in octets, longer lines are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Returns a new random feed secret for a user.
+pub fn new_feed_secret() -> Vec<u8> {
+    let mut feed_secret = vec![0; FEED_SECRET_BYTES];
+    rand::thread_rng().fill(&mut feed_secret[..]);
+    feed_secret
+}
+
+/// Returns the token of the calendar feed of a user, derived from the token
+/// secret and the feed secret of the user. Replacing the feed secret revokes
+/// the feed URL of that user, changing the token secret revokes all of them.
+pub fn feed_token(user_id: usize, feed_secret: &[u8], secret: &[u8]) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(secret);
+    hasher.update(format!(":calendar:{}:", user_id).as_bytes());
+    hasher.update(feed_secret);
+    hasher.finalize()[..TOKEN_BYTES]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Compares a token from a request with the expected token in constant time.
+pub fn verify_token(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// All-day event of a list in the calendar feed.
+pub struct Event {
+    pub list_id: usize,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: Vec<String>,
+    /// Whether the user joined the list, other lists are tentative.
+    pub joined: bool,
+}
+
+/// Writes an iCalendar (`.ics`) document with the given events.
+pub fn write_ics(events: &[Event], now: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    let mut line = |line: String| {
+        ics.push_str(&fold(&line));
+        ics.push_str("\r\n");
+    };
+    line("BEGIN:VCALENDAR".to_string());
+    line("VERSION:2.0".to_string());
+    line("PRODID:-//lunch-list//lunch-list//EN".to_string());
+    line("X-WR-CALNAME:Lunch List".to_string());
+    for event in events {
+        line("BEGIN:VEVENT".to_string());
+        line(format!("UID:list-{}@lunch-list", event.list_id));
+        line(format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")));
+        line(format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.date.format("%Y%m%d")
+        ));
+        line(format!(
+            "DTEND;VALUE=DATE:{}",
+            (event.date + Duration::days(1)).format("%Y%m%d")
+        ));
+        line(format!("SUMMARY:{}", escape(&event.summary)));
+        if !event.description.is_empty() {
+            line(format!(
+                "DESCRIPTION:{}",
+                escape(&event.description.join("\n"))
+            ));
+        }
+        line(format!(
+            "STATUS:{}",
+            if event.joined {
+                "CONFIRMED"
+            } else {
+                "TENTATIVE"
+            }
+        ));
+        line("TRANSP:TRANSPARENT".to_string());
+        line("END:VEVENT".to_string());
+    }
+    line("END:VCALENDAR".to_string());
+    ics
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into lines of at most 75 octets, continuation lines
+/// start with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::holiday::parse_ics;
+
+    #[test]
+    fn test_write_ics() {
+        let event = Event {
+            list_id: 3,
+            date: NaiveDate::from_ymd_opt(2020, 12, 24).unwrap(),
+            summary: "✔ Dinner, with dessert".to_string(),
+            description: vec!["x".repeat(100)],
+            joined: true,
+        };
+        let now = Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap();
+        let ics = write_ics(&[event], now);
+
+        assert!(ics.contains("DTSTART;VALUE=DATE:20201224\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20201225\r\n"));
+        assert!(ics.contains("SUMMARY:✔ Dinner\\, with dessert\r\n"));
+        assert!(ics.contains("STATUS:CONFIRMED\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        // The holiday importer reads the same format.
+        let events = parse_ics(&ics).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "✔ Dinner, with dessert");
+    }
+
+    #[test]
+    fn test_feed_token() {
+        let feed_secret = new_feed_secret();
+        let token = feed_token(1, &feed_secret, b"secret");
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert!(verify_token(
+            &token,
+            &feed_token(1, &feed_secret, b"secret")
+        ));
+        assert!(!verify_token(
+            &token,
+            &feed_token(2, &feed_secret, b"secret")
+        ));
+        assert!(!verify_token(
+            &token,
+            &feed_token(1, &feed_secret, b"other")
+        ));
+        assert!(!verify_token(
+            &token,
+            &feed_token(1, &new_feed_secret(), b"secret")
+        ));
+    }
+}
@@ -0,0 +1,15 @@
+use actix_web::web;
+
+mod feed;
+mod routes;
+
+/// The feed itself is protected by a token in its URL instead of the
+/// `Authorization` header, since calendar apps cannot log in.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/calendar")
+            .service(routes::get_feed_url)
+            .service(routes::reset_feed_url)
+            .service(routes::get_feed),
+    );
+}
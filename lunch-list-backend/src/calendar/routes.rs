@@ -0,0 +1,124 @@
+use actix_web::{get, post, web, HttpResponse, Responder};
+use chrono::{Duration, Local, Utc};
+use serde_json::json;
+
+use super::feed::{feed_token, new_feed_secret, verify_token, write_ics, Event};
+use crate::{auth::Claims, errors::ServiceError, store::Store, AppState};
+
+/// Number of days ahead of today that are included in the feed.
+const FEED_DAYS: i64 = 90;
+
+/// Returns the response with the feed URL of a user.
+fn feed_url(user_id: usize, feed_secret: &[u8], state: &AppState) -> HttpResponse {
+    let token = feed_token(user_id, feed_secret, state.token_secret.as_bytes());
+    HttpResponse::Ok().json(json!({
+        "url": format!("/api/calendar/{}/{}.ics", user_id, token)
+    }))
+}
+
+/// Returns the URL of the calendar feed of the caller, the feed secret is
+/// created on first use.
+#[get("")]
+async fn get_feed_url(
+    claims: Claims,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, ServiceError> {
+    let feed_secret = match store.feed_secret(claims.sub).await? {
+        Some(feed_secret) => feed_secret,
+        None => {
+            let feed_secret = new_feed_secret();
+            if !store.set_feed_secret(claims.sub, &feed_secret).await? {
+                return Err(ServiceError::Unauthorized);
+            }
+            feed_secret
+        }
+    };
+    Ok(feed_url(claims.sub, &feed_secret, &state))
+}
+
+/// Replaces the feed secret of the caller, which revokes the previous feed
+/// URL, and returns the new URL.
+#[post("/reset")]
+async fn reset_feed_url(
+    claims: Claims,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, ServiceError> {
+    let feed_secret = new_feed_secret();
+    if !store.set_feed_secret(claims.sub, &feed_secret).await? {
+        return Err(ServiceError::Unauthorized);
+    }
+    Ok(feed_url(claims.sub, &feed_secret, &state))
+}
+
+/// Returns the upcoming lists as all-day events, the lists the user joined
+/// are confirmed and marked with a check mark.
+#[get("/{user_id}/{token}.ics")]
+async fn get_feed(
+    path: web::Path<(usize, String)>,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, ServiceError> {
+    let (user_id, token) = path.into_inner();
+    let feed_secret = store
+        .feed_secret(user_id)
+        .await?
+        .ok_or(ServiceError::Unauthorized)?;
+    let expected = feed_token(user_id, &feed_secret, state.token_secret.as_bytes());
+    if !verify_token(&token, &expected) {
+        return Err(ServiceError::Unauthorized);
+    }
+
+    let today = Local::now().naive_local().date();
+    let mut events = Vec::new();
+    for id in store
+        .list_ids_between(Some(today), Some(today + Duration::days(FEED_DAYS)), None)
+        .await?
+    {
+        let list = match store.list(id).await? {
+            Some(list) => list,
+            None => continue,
+        };
+        let joined = store.attendee_ids(id).await?.contains(&user_id);
+        let waitlisted = store.waitlist_ids(id).await?.contains(&user_id);
+        let name = list
+            .title
+            .clone()
+            .or_else(|| state.list_types.name(&list.list_type).map(str::to_string))
+            .unwrap_or_else(|| list.list_type.to_string());
+
+        let mut description = Vec::new();
+        if joined {
+            description.push("You joined this list.".to_string());
+        } else if waitlisted {
+            description.push("You are on the waitlist.".to_string());
+        }
+        if let Some(cook) = &list.cook {
+            description.push(format!("Cooked by {}", cook));
+        }
+        if let Some(deadline) = list.deadline {
+            let deadline = deadline.with_timezone(&Local);
+            description.push(format!("Sign-up closes at {}", deadline.format("%A %H:%M")));
+        }
+        if let Some(menu) = &list.menu {
+            description.push(menu.clone());
+        }
+
+        events.push(Event {
+            list_id: id,
+            date: list.date,
+            summary: if joined {
+                format!("✔ {}", name)
+            } else {
+                name
+            },
+            description,
+            joined,
+        });
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(write_ics(&events, Utc::now())))
+}
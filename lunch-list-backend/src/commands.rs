@@ -0,0 +1,354 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, IsTerminal, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Clap;
+
+use lunch_list_backend::{
+    auth::{hash_password, validate_username},
+    holiday::{parse_ics, Holiday},
+    list::{List, ListType, ListTypes},
+    report::{Report, ReportFormat},
+    store::{
+        dump::{self, Dump, DUMP_VERSION},
+        Store,
+    },
+};
+
+#[derive(Clap)]
+pub enum Command {
+    /// Upgrade stored data to the latest schema version
+    Migrate,
+    /// Write all users, lists and attendance as JSON to stdout
+    Export,
+    /// Load users, lists and attendance from a JSON file created by `export`
+    Import {
+        /// JSON file to import
+        file: PathBuf,
+    },
+    /// Manage user accounts
+    User(UserCommand),
+    /// Manage lists
+    List(ListCommand),
+    /// Manage holidays on which no lists can be created
+    Holiday(HolidayCommand),
+    /// Write the attendance per list and per user over a range of dates
+    Report {
+        /// First date of the report, e.g. 2020-12-01
+        #[clap(long)]
+        start_date: NaiveDate,
+        /// Last date of the report, e.g. 2020-12-31
+        #[clap(long)]
+        end_date: NaiveDate,
+        /// Only include lists of this type
+        #[clap(long = "type")]
+        list_type: Option<String>,
+        /// Format of the report, csv or xlsx
+        #[clap(long, default_value = "csv")]
+        format: ReportFormat,
+        /// File to write the report to instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clap)]
+pub enum UserCommand {
+    /// Create a new user, the password is read from stdin
+    Add { username: String },
+    /// Delete a user and remove it from all lists
+    Delete { username: String },
+    /// Set a new password for a user, the password is read from stdin
+    ResetPassword { username: String },
+}
+
+#[derive(Clap)]
+pub enum ListCommand {
+    /// Create a new list
+    Create {
+        /// Date of the list, e.g. 2020-12-24
+        #[clap(long)]
+        date: NaiveDate,
+        /// Type of the list, must be one of the configured list types
+        #[clap(long = "type")]
+        list_type: String,
+        /// Sign-up deadline of the list, e.g. 2020-12-24T15:00:00+01:00
+        #[clap(long)]
+        deadline: Option<DateTime<Utc>>,
+    },
+    /// Delete a list and its attendance
+    Delete { id: usize },
+}
+
+#[derive(Clap)]
+pub enum HolidayCommand {
+    /// Show all holidays
+    List,
+    /// Add a holiday, replacing the name of an existing holiday on that date
+    Add {
+        /// Date of the holiday, e.g. 2020-12-25
+        #[clap(long)]
+        date: NaiveDate,
+        /// Name of the holiday, e.g. "Christmas"
+        #[clap(long)]
+        name: String,
+    },
+    /// Delete a holiday
+    Delete {
+        /// Date of the holiday, e.g. 2020-12-25
+        #[clap(long)]
+        date: NaiveDate,
+    },
+    /// Add all events of an iCalendar (.ics) file as holidays
+    Import {
+        /// iCalendar file to import
+        file: PathBuf,
+    },
+}
+
+pub async fn run(
+    command: Command,
+    store: Arc<dyn Store>,
+    list_types: &ListTypes,
+) -> io::Result<()> {
+    if !matches!(command, Command::Migrate) {
+        check_migrations(store.as_ref()).await?;
+    }
+    match command {
+        Command::Migrate => migrate(store.as_ref()).await,
+        Command::Export => export(store.as_ref()).await,
+        Command::Import { file } => import(store.as_ref(), file).await,
+        Command::User(command) => user(store.as_ref(), command).await,
+        Command::List(command) => list(store.as_ref(), command, list_types).await,
+        Command::Holiday(command) => holiday(store.as_ref(), command).await,
+        Command::Report {
+            start_date,
+            end_date,
+            list_type,
+            format,
+            output,
+        } => {
+            let list_type = match list_type {
+                Some(list_type) => Some(
+                    list_type
+                        .parse::<ListType>()
+                        .map_err(|_| invalid_input("invalid list type"))?,
+                ),
+                None => None,
+            };
+            let report = Report::build(store.as_ref(), start_date, end_date, list_type.as_ref())
+                .await
+                .map_err(store_error)?;
+            let bytes = report.write(format).map_err(store_error)?;
+            match output {
+                Some(path) => fs::write(path, bytes)?,
+                None => io::stdout().write_all(&bytes)?,
+            }
+            log::info!(
+                "Wrote report of {} list(s) and {} user(s)",
+                report.days.len(),
+                report.users.len()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Fails when stored data has to be migrated before it can be used.
+pub async fn check_migrations(store: &dyn Store) -> io::Result<()> {
+    let pending = store.pending_migrations().await.map_err(store_error)?;
+    if pending > 0 {
+        return Err(io::Error::other(format!(
+            "stored data is {} migration(s) behind, run `ll migrate` first",
+            pending
+        )));
+    }
+    Ok(())
+}
+
+async fn migrate(store: &dyn Store) -> io::Result<()> {
+    let applied = store.migrate().await.map_err(store_error)?;
+    log::info!("Applied {} migration(s), data is up to date", applied);
+    Ok(())
+}
+
+async fn export(store: &dyn Store) -> io::Result<()> {
+    let dump = dump::export(store).await.map_err(store_error)?;
+    serde_json::to_writer_pretty(io::stdout(), &dump)?;
+    println!();
+    log::info!(
+        "Exported {} user(s) and {} list(s)",
+        dump.users.len(),
+        dump.lists.len()
+    );
+    Ok(())
+}
+
+async fn import(store: &dyn Store, file: PathBuf) -> io::Result<()> {
+    let dump: Dump = serde_json::from_reader(File::open(file)?)?;
+    if dump.version != DUMP_VERSION {
+        return Err(io::Error::other(format!(
+            "unsupported dump version {}, expected {}",
+            dump.version, DUMP_VERSION
+        )));
+    }
+    if !dump::export(store).await.map_err(store_error)?.is_empty() {
+        return Err(io::Error::other("can only import into an empty store"));
+    }
+
+    let (users, lists) = (dump.users.len(), dump.lists.len());
+    dump::import(store, dump).await.map_err(store_error)?;
+    log::info!("Imported {} user(s) and {} list(s)", users, lists);
+    Ok(())
+}
+
+async fn user(store: &dyn Store, command: UserCommand) -> io::Result<()> {
+    match command {
+        UserCommand::Add { username } => {
+            let username = username.trim();
+            if username.is_empty() || validate_username(username).is_err() {
+                return Err(invalid_input(
+                    "username can only contain alphanumeric characters and spaces",
+                ));
+            }
+            let hash = hash_password(&read_password()?).map_err(store_error)?;
+            let id = store
+                .create_user(username, &hash)
+                .await
+                .map_err(store_error)?;
+            log::info!("Created user '{}' with id {}", username, id);
+        }
+        UserCommand::Delete { username } => {
+            let id = user_id(store, &username).await?;
+            store.delete_user(id).await.map_err(store_error)?;
+            log::info!("Deleted user '{}'", username);
+        }
+        UserCommand::ResetPassword { username } => {
+            let id = user_id(store, &username).await?;
+            let hash = hash_password(&read_password()?).map_err(store_error)?;
+            store.set_password(id, &hash).await.map_err(store_error)?;
+            store.clear_refresh_tokens(id).await.map_err(store_error)?;
+            log::info!("Changed password of user '{}'", username);
+        }
+    }
+    Ok(())
+}
+
+async fn list(store: &dyn Store, command: ListCommand, list_types: &ListTypes) -> io::Result<()> {
+    match command {
+        ListCommand::Create {
+            date,
+            list_type,
+            deadline,
+        } => {
+            let list_type = list_type
+                .parse::<ListType>()
+                .ok()
+                .filter(|list_type| list_types.contains(list_type))
+                .ok_or_else(|| invalid_input("unknown list type"))?;
+            if let Some(holiday) = store.holiday(date).await.map_err(store_error)? {
+                return Err(io::Error::other(format!(
+                    "{} is a holiday: {}",
+                    date, holiday.name
+                )));
+            }
+            let mut list = List::new(0, list_type, date);
+            list.deadline = deadline;
+            let id = store.create_list(&list).await.map_err(store_error)?;
+            log::info!("Created list with id {}", id);
+        }
+        ListCommand::Delete { id } => {
+            if !store.delete_list(id).await.map_err(store_error)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("list {} does not exist", id),
+                ));
+            }
+            log::info!("Deleted list {}", id);
+        }
+    }
+    Ok(())
+}
+
+async fn holiday(store: &dyn Store, command: HolidayCommand) -> io::Result<()> {
+    match command {
+        HolidayCommand::List => {
+            for holiday in store.holidays().await.map_err(store_error)? {
+                println!("{}\t{}", holiday.date, holiday.name);
+            }
+        }
+        HolidayCommand::Add { date, name } => {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(invalid_input("name cannot be empty"));
+            }
+            store
+                .add_holiday(&Holiday { date, name })
+                .await
+                .map_err(store_error)?;
+            log::info!("Added holiday on {}", date);
+        }
+        HolidayCommand::Delete { date } => {
+            if !store.delete_holiday(date).await.map_err(store_error)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no holiday on {}", date),
+                ));
+            }
+            log::info!("Deleted holiday on {}", date);
+        }
+        HolidayCommand::Import { file } => {
+            let holidays = parse_ics(&fs::read_to_string(file)?)?;
+            for holiday in &holidays {
+                store.add_holiday(holiday).await.map_err(store_error)?;
+            }
+            log::info!("Imported {} holiday(s)", holidays.len());
+        }
+    }
+    Ok(())
+}
+
+async fn user_id(store: &dyn Store, username: &str) -> io::Result<usize> {
+    store
+        .user_id(username.trim())
+        .await
+        .map_err(store_error)?
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("user '{}' does not exist", username),
+            )
+        })
+}
+
+/// Reads a non-empty password from the first line of stdin, without echoing
+/// it when stdin is a terminal.
+fn read_password() -> io::Result<String> {
+    let password = if io::stdin().is_terminal() {
+        rpassword::prompt_password("Password: ")?
+    } else {
+        let mut password = String::new();
+        io::stdin().lock().read_line(&mut password)?;
+        password.trim_end_matches(&['\r', '\n'][..]).to_string()
+    };
+    if password.is_empty() {
+        Err(invalid_input("password cannot be empty"))
+    } else {
+        Ok(password)
+    }
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Converts errors from the store, which only display as a generic server
+/// error, into an `io::Error` with the full error message.
+pub fn store_error<E: fmt::Debug>(e: E) -> io::Error {
+    io::Error::other(format!("{:?}", e))
+}
@@ -5,6 +5,7 @@ use actix_web::{
     http::{header, StatusCode},
     HttpResponse,
 };
+use chrono::NaiveDate;
 use jsonwebtoken::errors::Error as JwtError;
 use mobc_redis::redis::RedisError;
 use serde_json::json;
@@ -16,6 +17,8 @@ pub enum ServiceError {
     InternalError,
     #[error("Internal Server Error")]
     DatabaseError(#[from] RedisError),
+    #[error("Internal Server Error")]
+    SqliteError(#[from] rusqlite::Error),
     #[error("Unauthorized")]
     Unauthorized,
     #[error("Invalid JWT")]
@@ -36,12 +39,35 @@ pub enum ServiceError {
     InvalidRefreshToken,
     #[error("Invalid signup secret")]
     InvalidSignupSecret,
+    #[error("Unknown list type '{list_type}'")]
+    UnknownListType { list_type: String },
+    #[error("A list of this type already exists on this date")]
+    ListAlreadyExists { id: usize },
+    #[error("The sign-up deadline of this list has passed")]
+    DeadlinePassed,
+    #[error("No lists can be created on {date}: {name}")]
+    DateClosed { date: NaiveDate, name: String },
+    #[error("Unknown user '{username}'")]
+    UnknownUser { username: String },
+    #[error("The list has no attendees to split the costs between")]
+    NothingToSplit,
+    #[error("The list has no attendees to choose a cook from")]
+    NoCookCandidates,
+    #[error("Unknown recipe {id}")]
+    UnknownRecipe { id: usize },
+    #[error("The list has no recipe")]
+    NoRecipe,
+    #[error("The end date cannot be before the start date")]
+    InvalidDateRange,
+    #[error("Not enough seats left on this list for your guests")]
+    NotEnoughSeats,
 }
 
 impl ResponseError for ServiceError {
     fn error_response(&self) -> HttpResponse {
         let json = match self {
             Self::ValidatorError(errors) => json!({ "error": self.to_string(), "errors": errors }),
+            Self::ListAlreadyExists { id } => json!({ "error": self.to_string(), "id": id }),
             _ => json!({ "error": self.to_string()}),
         };
         HttpResponse::build(self.status_code()).json(json)
@@ -56,7 +82,17 @@ impl ResponseError for ServiceError {
             Self::InvalidJwt(_)
             | Self::UserAlreadyExists { .. }
             | Self::InvalidHeader
-            | Self::ValidatorError { .. } => StatusCode::BAD_REQUEST,
+            | Self::ValidatorError { .. }
+            | Self::UnknownListType { .. }
+            | Self::DateClosed { .. }
+            | Self::UnknownUser { .. }
+            | Self::NothingToSplit
+            | Self::NoCookCandidates
+            | Self::UnknownRecipe { .. }
+            | Self::NoRecipe
+            | Self::InvalidDateRange => StatusCode::BAD_REQUEST,
+            Self::ListAlreadyExists { .. } | Self::NotEnoughSeats => StatusCode::CONFLICT,
+            Self::DeadlinePassed => StatusCode::FORBIDDEN,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
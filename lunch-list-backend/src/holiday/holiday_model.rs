@@ -0,0 +1,9 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Date on which no lists can be created, e.g. a public holiday.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
@@ -0,0 +1,126 @@
+use std::io;
+
+use chrono::{Duration, NaiveDate};
+
+use super::holiday_model::Holiday;
+
+/// Longest event that is imported, to guard against broken calendars.
+const MAX_EVENT_DAYS: i64 = 366;
+
+/// Parses the events of an iCalendar (`.ics`) file into holidays, one for
+/// every day an event spans. Events without a summary are named "Closed".
+pub fn parse_ics(input: &str) -> io::Result<Vec<Holiday>> {
+    let mut holidays = Vec::new();
+    let mut event: Option<Event> = None;
+    for (number, line) in unfold(input).iter().enumerate() {
+        let (name, value) = match line.split_once(':') {
+            Some((property, value)) => (property.split(';').next().unwrap_or(property), value),
+            None => continue,
+        };
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event::default())
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let event = event.take().unwrap_or_default();
+                let start = event.start.ok_or_else(|| {
+                    invalid_data(format!("event without DTSTART at line {}", number + 1))
+                })?;
+                let end = event.end.unwrap_or(start + Duration::days(1));
+                let days = (end - start).num_days().clamp(1, MAX_EVENT_DAYS);
+                let name = event.summary.unwrap_or_else(|| "Closed".to_string());
+                holidays.extend((0..days).map(|day| Holiday {
+                    date: start + Duration::days(day),
+                    name: name.clone(),
+                }));
+            }
+            ("DTSTART", Some(event)) => event.start = Some(parse_date(value, number)?),
+            ("DTEND", Some(event)) => event.end = Some(parse_date(value, number)?),
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape(value)),
+            _ => {}
+        }
+    }
+    Ok(holidays)
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    summary: Option<String>,
+}
+
+/// Joins folded content lines, which continue with a space or a tab.
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Parses the date of a `DATE` or `DATE-TIME` value, e.g. `20201225` or
+/// `20201225T120000Z`.
+fn parse_date(value: &str, number: usize) -> io::Result<NaiveDate> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| invalid_data(format!("invalid date '{}' at line {}", value, number + 1)))
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_string()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART;VALUE=DATE:20201225\r\n\
+                   DTEND;VALUE=DATE:20201227\r\n\
+                   SUMMARY:Christmas\\, closed\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART:20210101T000000Z\r\n\
+                   SUMMARY:New\r\n  Year\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let date = |d| NaiveDate::from_ymd_opt(2020, 12, d).unwrap();
+        assert_eq!(
+            parse_ics(ics).unwrap(),
+            vec![
+                Holiday {
+                    date: date(25),
+                    name: "Christmas, closed".to_string(),
+                },
+                Holiday {
+                    date: date(26),
+                    name: "Christmas, closed".to_string(),
+                },
+                Holiday {
+                    date: NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+                    name: "New Year".to_string(),
+                },
+            ]
+        );
+        assert!(parse_ics("BEGIN:VEVENT\nDTSTART:2020\nEND:VEVENT").is_err());
+    }
+}
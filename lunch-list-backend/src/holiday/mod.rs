@@ -0,0 +1,13 @@
+use actix_web::web;
+
+mod holiday_model;
+mod ics;
+mod routes;
+
+pub use holiday_model::Holiday;
+pub use ics::parse_ics;
+
+/// Holidays are managed with `ll holiday`, the API only lists them.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/holiday").service(routes::get_holidays));
+}
@@ -0,0 +1,13 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde_json::json;
+
+use crate::{auth::Claims, errors::ServiceError, store::Store};
+
+#[get("")]
+async fn get_holidays(
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let holidays = store.holidays().await?;
+    Ok(HttpResponse::Ok().json(json!({ "holidays": holidays })))
+}
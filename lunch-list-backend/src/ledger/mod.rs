@@ -0,0 +1,95 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use validator_derive::Validate;
+
+use crate::{errors::ServiceError, store::Store};
+
+mod routes;
+mod transaction;
+
+pub use transaction::{balances, Transaction};
+
+/// Body of `POST /api/list/{id}/expense`, the amount is in cents.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExpenseRequest {
+    #[validate(range(
+        min = 1,
+        max = 10_000_000,
+        message = "Amount must be between 1 and 10000000 cents"
+    ))]
+    pub amount: i64,
+    #[serde(deserialize_with = "deserialize_trimmed")]
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Description must be between 1 and 100 characters"
+    ))]
+    pub description: String,
+}
+
+/// Trims a string before it is validated, so that blank strings are empty.
+fn deserialize_trimmed<'de, D>(d: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(d)?;
+    Ok(value.trim().to_string())
+}
+
+/// Transaction with usernames instead of user ids, as returned by the API.
+#[derive(Serialize)]
+pub struct TransactionView {
+    id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    list_id: Option<usize>,
+    description: String,
+    created: DateTime<Utc>,
+    amounts: BTreeMap<String, i64>,
+}
+
+/// Replaces the user ids of transactions with usernames, users that no longer
+/// exist are left out.
+pub async fn with_usernames(
+    store: &dyn Store,
+    transactions: Vec<Transaction>,
+) -> Result<Vec<TransactionView>, ServiceError> {
+    let user_ids: BTreeSet<usize> = transactions
+        .iter()
+        .flat_map(|transaction| transaction.amounts.keys().copied())
+        .collect();
+    let mut usernames = HashMap::new();
+    for user_id in user_ids {
+        if let Some(user) = store.user(user_id).await? {
+            usernames.insert(user_id, user.username);
+        }
+    }
+
+    let mut views = Vec::new();
+    for transaction in transactions {
+        let amounts = transaction
+            .amounts
+            .into_iter()
+            .filter_map(|(user_id, amount)| Some((usernames.get(&user_id)?.clone(), amount)))
+            .collect();
+        views.push(TransactionView {
+            id: transaction.id,
+            list_id: transaction.list_id,
+            description: transaction.description,
+            created: transaction.created,
+            amounts,
+        });
+    }
+    Ok(views)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/ledger")
+            .service(routes::get_transactions)
+            .service(routes::get_balances)
+            .service(routes::post_settlement),
+    );
+}
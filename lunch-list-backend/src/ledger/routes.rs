@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use actix_web::{get, post, web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+use validator_derive::Validate;
+
+use super::{transaction::balances, with_usernames, Transaction};
+use crate::{auth::Claims, errors::ServiceError, store::Store};
+
+/// Body of `POST /api/ledger/settlement`, the caller paid `amount` cents to
+/// the user `to`.
+#[derive(Debug, Deserialize, Validate)]
+struct SettlementRequest {
+    to: String,
+    #[validate(range(
+        min = 1,
+        max = 10_000_000,
+        message = "Amount must be between 1 and 10000000 cents"
+    ))]
+    amount: i64,
+}
+
+#[get("")]
+async fn get_transactions(
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let transactions = with_usernames(store.as_ref(), store.transactions().await?).await?;
+    Ok(HttpResponse::Ok().json(json!({ "transactions": transactions })))
+}
+
+#[get("/balance")]
+async fn get_balances(
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let transactions = store.transactions().await?;
+    let mut result = Vec::new();
+    for (user_id, balance) in balances(&transactions) {
+        if let Some(user) = store.user(user_id).await? {
+            result.push((user.username, balance));
+        }
+    }
+    result.sort();
+    let balances: Vec<_> = result
+        .into_iter()
+        .map(|(username, balance)| json!({ "username": username, "balance": balance }))
+        .collect();
+    Ok(HttpResponse::Ok().json(json!({ "balances": balances })))
+}
+
+#[post("/settlement")]
+async fn post_settlement(
+    settlement: web::Json<SettlementRequest>,
+    claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    settlement.validate()?;
+    let to =
+        store
+            .user_id(settlement.to.trim())
+            .await?
+            .ok_or_else(|| ServiceError::UnknownUser {
+                username: settlement.to.clone(),
+            })?;
+    if to == claims.sub {
+        let mut errors = HashMap::new();
+        errors.insert("to".to_string(), "Cannot settle with yourself".to_string());
+        return Err(ServiceError::ValidatorError(errors));
+    }
+
+    let mut transaction = Transaction::settlement(claims.sub, to, settlement.amount);
+    transaction.id = store.add_transaction(&transaction).await?;
+    let view = with_usernames(store.as_ref(), vec![transaction]).await?;
+    Ok(HttpResponse::Created().json(&view[0]))
+}
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Entry of the balance ledger, moving amounts between users. The amounts of
+/// a transaction add up to zero, a positive balance means that others owe
+/// the user money.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Transaction {
+    pub id: usize,
+    /// List of an expense, `None` for settlements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_id: Option<usize>,
+    pub description: String,
+    pub created: DateTime<Utc>,
+    /// Change of the balance of each user in cents.
+    pub amounts: BTreeMap<usize, i64>,
+}
+
+impl Transaction {
+    /// Creates an expense on a list paid by `payer`, split evenly across the
+    /// attendees. Every attendee pays one share for themselves and one for
+    /// each guest, left over cents are paid by the attendees with the lowest
+    /// ids. Returns `None` when there are no shares.
+    pub fn expense(
+        list_id: usize,
+        payer: usize,
+        amount: i64,
+        description: String,
+        shares: &BTreeMap<usize, usize>,
+    ) -> Option<Self> {
+        let total = shares.values().sum::<usize>() as i64;
+        if total == 0 {
+            return None;
+        }
+
+        let (share, mut remainder) = (amount / total, amount % total);
+        let mut amounts = BTreeMap::new();
+        amounts.insert(payer, amount);
+        for (user_id, count) in shares {
+            let count = *count as i64;
+            let extra = remainder.min(count);
+            remainder -= extra;
+            *amounts.entry(*user_id).or_default() -= share * count + extra;
+        }
+        amounts.retain(|_, amount| *amount != 0);
+
+        Some(Self {
+            id: 0,
+            list_id: Some(list_id),
+            description,
+            created: Utc::now(),
+            amounts,
+        })
+    }
+
+    /// Creates a settlement of `amount` paid by `from` to `to`.
+    pub fn settlement(from: usize, to: usize, amount: i64) -> Self {
+        let mut amounts = BTreeMap::new();
+        amounts.insert(from, amount);
+        amounts.insert(to, -amount);
+        Self {
+            id: 0,
+            list_id: None,
+            description: "Settlement".to_string(),
+            created: Utc::now(),
+            amounts,
+        }
+    }
+}
+
+/// Returns the balance of every user that appears in the transactions.
+pub fn balances<'a, I>(transactions: I) -> HashMap<usize, i64>
+where
+    I: IntoIterator<Item = &'a Transaction>,
+{
+    let mut balances = HashMap::new();
+    for transaction in transactions {
+        for (user_id, amount) in &transaction.amounts {
+            *balances.entry(*user_id).or_default() += amount;
+        }
+    }
+    balances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expense_split() {
+        // User 1 brings two guests, so 1001 cents are split in four shares.
+        let shares: BTreeMap<usize, usize> = vec![(1, 3), (2, 1)].into_iter().collect();
+        let expense = Transaction::expense(7, 2, 1001, "Groceries".to_string(), &shares).unwrap();
+        let expected: BTreeMap<usize, i64> = vec![(1, -751), (2, 751)].into_iter().collect();
+        assert_eq!(expense.amounts, expected);
+
+        let settlement = Transaction::settlement(1, 2, 751);
+        assert!(balances(&[expense, settlement])
+            .values()
+            .all(|balance| *balance == 0));
+
+        assert!(Transaction::expense(7, 2, 1000, String::new(), &BTreeMap::new()).is_none());
+    }
+}
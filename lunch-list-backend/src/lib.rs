@@ -1,19 +1,44 @@
 #![forbid(unsafe_code)]
-use actix_web::{web::HttpResponse, Responder};
+use actix_web::{web, web::HttpResponse, Responder};
 use serde_json::json;
 
 pub mod auth;
+mod calendar;
 mod errors;
+pub mod holiday;
+pub mod ledger;
 pub mod list;
+pub mod recipe;
+pub mod report;
+mod stats;
+pub mod store;
+pub mod template;
 pub mod user;
 
-type Pool = mobc::Pool<mobc_redis::RedisConnectionManager>;
-
 pub struct AppState {
     pub token_secret: String,
     pub signup_secret: String,
+    pub list_types: list::ListTypes,
 }
 
 pub async fn not_found() -> impl Responder {
     HttpResponse::NotFound().json(json!({ "error": "Resource not found" }))
 }
+
+/// Registers all API routes under the `/api` scope.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api")
+            .configure(auth::config)
+            .configure(calendar::config)
+            .configure(holiday::config)
+            .configure(ledger::config)
+            .configure(list::config)
+            .configure(recipe::config)
+            .configure(report::config)
+            .configure(stats::config)
+            .configure(template::config)
+            .configure(user::config)
+            .default_service(web::route().to(not_found)),
+    );
+}
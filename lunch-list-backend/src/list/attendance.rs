@@ -0,0 +1,121 @@
+use std::{borrow::Cow, cmp::max, collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use validator::ValidationError;
+use validator_derive::Validate;
+
+const MAX_GUESTS: usize = 20;
+
+/// Maximum number of characters in the name of a guest.
+const MAX_GUEST_NAME_CHARS: usize = 50;
+
+/// Guests that an attendee brings along.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Guests {
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+}
+
+/// Answer of a user to a list.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rsvp {
+    #[default]
+    Yes,
+    No,
+    Maybe,
+}
+
+/// Number of answers per state, `yes` includes the guests of attendees.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RsvpCounts {
+    pub yes: usize,
+    pub maybe: usize,
+    pub no: usize,
+}
+
+/// Optional body of `PUT /api/list/{id}/user`.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct AttendanceRequest {
+    #[serde(default)]
+    rsvp: Rsvp,
+    #[serde(default)]
+    #[validate(range(max = 20, message = "Cannot bring more than 20 guests"))]
+    guests: usize,
+    #[serde(default)]
+    #[validate(
+        length(max = 20, message = "Cannot bring more than 20 guests"),
+        custom = "validate_guest_names"
+    )]
+    guest_names: Vec<String>,
+    #[validate(length(max = 100, message = "Note cannot be longer than 100 characters"))]
+    note: Option<String>,
+}
+
+/// Returns an error when the name of a guest is too long.
+fn validate_guest_names(names: &[String]) -> Result<(), ValidationError> {
+    if names
+        .iter()
+        .all(|name| name.trim().chars().count() <= MAX_GUEST_NAME_CHARS)
+    {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            code: Cow::from("guest_name_length"),
+            message: Some(Cow::from("Guest names cannot be longer than 50 characters")),
+            params: HashMap::new(),
+        })
+    }
+}
+
+impl fmt::Display for Rsvp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::Maybe => "maybe",
+        })
+    }
+}
+
+impl FromStr for Rsvp {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yes" => Ok(Self::Yes),
+            "no" => Ok(Self::No),
+            "maybe" => Ok(Self::Maybe),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AttendanceRequest {
+    pub fn rsvp(&self) -> Rsvp {
+        self.rsvp
+    }
+
+    /// Returns the guests of the request, unnamed guests are only counted.
+    pub fn guests(&self) -> Guests {
+        let names: Vec<String> = self
+            .guest_names
+            .iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        Guests {
+            count: max(self.guests, names.len()).min(MAX_GUESTS),
+            names,
+        }
+    }
+
+    /// Returns the note of the request, empty notes are ignored.
+    pub fn note(&self) -> Option<&str> {
+        self.note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+    }
+}
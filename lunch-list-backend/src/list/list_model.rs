@@ -1,22 +1,62 @@
 use std::collections::{HashMap, HashSet};
 
-use chrono::naive::NaiveDate;
+use chrono::{naive::NaiveDate, DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use validator_derive::Validate;
 
-use super::list_type::ListType;
+use super::{
+    attendance::{Guests, Rsvp, RsvpCounts},
+    list_type::ListType,
+};
+use crate::user::DietSummary;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize, Validate)]
 pub struct List {
     #[serde(skip_deserializing)]
     pub id: usize,
-    #[serde(rename = "type", flatten)]
+    #[serde(rename = "type")]
     pub list_type: ListType,
     #[serde(rename = "date")]
     pub date: NaiveDate,
+    /// Time after which users can no longer join or leave the list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+    /// Maximum number of attendees including their guests, further users are
+    /// put on the waitlist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, message = "Capacity must be at least 1"))]
+    pub capacity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 100, message = "Title cannot be longer than 100 characters"))]
+    pub title: Option<String>,
+    /// Description of the menu, formatted as markdown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 5000, message = "Menu cannot be longer than 5000 characters"))]
+    pub menu: Option<String>,
+    /// Name of the person cooking, which counts for the cook rotation when it
+    /// matches a username exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 50, message = "Cook cannot be longer than 50 characters"))]
+    pub cook: Option<String>,
+    /// Id of the recipe that is cooked, see `GET /api/list/{id}/shopping`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe: Option<usize>,
     #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
-    size: Option<usize>,
+    counts: Option<RsvpCounts>,
     #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
     users: Option<HashSet<String>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    waitlist: Option<Vec<String>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    guests: Option<HashMap<String, Guests>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    maybe: Option<Vec<String>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    no: Option<Vec<String>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    notes: Option<HashMap<String, String>>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    diet: Option<DietSummary>,
 }
 
 impl List {
@@ -25,23 +65,43 @@ impl List {
             return None;
         }
 
-        let list_type = hash
-            .get("type")
-            .unwrap()
-            .parse::<ListType>()
-            .expect("list type must be either 'lunch' or 'dinner'");
-        let date = hash
-            .get("date")
-            .unwrap()
-            .parse::<NaiveDate>()
-            .expect("date not formatted as ISO 8601");
-        Some(Self {
+        let list_type = hash.get("type")?.parse::<ListType>().ok()?;
+        let date = hash.get("date")?.parse::<NaiveDate>().ok()?;
+        let mut list = Self::new(id, list_type, date);
+        list.deadline = hash.get("deadline").and_then(|d| d.parse().ok());
+        list.capacity = hash.get("capacity").and_then(|c| c.parse().ok());
+        list.title = hash.get("title").cloned();
+        list.menu = hash.get("menu").cloned();
+        list.cook = hash.get("cook").cloned();
+        list.recipe = hash.get("recipe").and_then(|r| r.parse().ok());
+        Some(list)
+    }
+
+    pub fn new(id: usize, list_type: ListType, date: NaiveDate) -> Self {
+        Self {
             id,
             list_type,
             date,
-            size: None,
+            deadline: None,
+            capacity: None,
+            title: None,
+            menu: None,
+            cook: None,
+            recipe: None,
+            counts: None,
             users: None,
-        })
+            waitlist: None,
+            guests: None,
+            maybe: None,
+            no: None,
+            notes: None,
+            diet: None,
+        }
+    }
+
+    /// Returns true when the deadline of the list has passed at `now`.
+    pub fn is_closed(&self, now: DateTime<Utc>) -> bool {
+        self.deadline.is_some_and(|deadline| now >= deadline)
     }
 
     pub fn with_users(mut self, users: HashSet<String>) -> Self {
@@ -49,8 +109,55 @@ impl List {
         self
     }
 
-    pub fn with_size(mut self, size: usize) -> Self {
-        self.size = Some(size);
+    pub fn with_waitlist(mut self, waitlist: Vec<String>) -> Self {
+        self.waitlist = Some(waitlist);
+        self
+    }
+
+    /// Adds the guests of attendees by username of their host.
+    pub fn with_guests(mut self, guests: HashMap<String, Guests>) -> Self {
+        self.guests = Some(guests);
+        self
+    }
+
+    /// Adds the notes of attendees by username.
+    pub fn with_notes(mut self, notes: HashMap<String, String>) -> Self {
+        self.notes = Some(notes);
+        self
+    }
+
+    /// Adds the summary of the dietary preferences of the attendees.
+    pub fn with_diet(mut self, diet: DietSummary) -> Self {
+        self.diet = Some(diet);
+        self
+    }
+
+    /// Adds the usernames of users who answered maybe or no.
+    pub fn with_answers(mut self, maybe: Vec<String>, no: Vec<String>) -> Self {
+        self.maybe = Some(maybe);
+        self.no = Some(no);
+        self
+    }
+
+    /// Sets the number of answers per state. The attendees and the guests
+    /// they bring count as yes, guests of waitlisted users are not counted.
+    pub fn with_counts(
+        mut self,
+        attendees: &HashSet<usize>,
+        guests: &HashMap<usize, Guests>,
+        answers: &HashMap<usize, Rsvp>,
+    ) -> Self {
+        let guest_count: usize = guests
+            .iter()
+            .filter(|(user_id, _)| attendees.contains(user_id))
+            .map(|(_, guests)| guests.count)
+            .sum();
+        let count = |rsvp| answers.values().filter(|answer| **answer == rsvp).count();
+        self.counts = Some(RsvpCounts {
+            yes: attendees.len() + guest_count,
+            maybe: count(Rsvp::Maybe),
+            no: count(Rsvp::No),
+        });
         self
     }
 }
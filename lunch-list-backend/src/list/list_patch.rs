@@ -0,0 +1,73 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer};
+
+use super::{list_model::List, list_type::ListType};
+
+/// Body of `PATCH /api/list/{id}`. Missing fields are left unchanged, the
+/// title, menu and cook are cleared when `null` or empty and the deadline,
+/// capacity and recipe when `null`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ListPatch {
+    #[serde(default, rename = "type")]
+    list_type: Option<ListType>,
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "present")]
+    deadline: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    capacity: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "present")]
+    title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    menu: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    cook: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    recipe: Option<Option<usize>>,
+}
+
+impl ListPatch {
+    /// Applies the changes to a list.
+    pub fn apply(self, list: &mut List) {
+        if let Some(list_type) = self.list_type {
+            list.list_type = list_type;
+        }
+        if let Some(date) = self.date {
+            list.date = date;
+        }
+        if let Some(deadline) = self.deadline {
+            list.deadline = deadline;
+        }
+        if let Some(capacity) = self.capacity {
+            list.capacity = capacity;
+        }
+        if let Some(title) = self.title {
+            list.title = non_empty(title);
+        }
+        if let Some(menu) = self.menu {
+            list.menu = non_empty(menu);
+        }
+        if let Some(cook) = self.cook {
+            list.cook = non_empty(cook);
+        }
+        if let Some(recipe) = self.recipe {
+            list.recipe = recipe;
+        }
+    }
+}
+
+/// Distinguishes a field that is `null` from a missing field, which is left
+/// `None` by `#[serde(default)]`.
+fn present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
@@ -1,7 +1,11 @@
 use std::cmp::min;
 
+use chrono::NaiveDate;
 use serde::Deserialize;
 
+use super::ListType;
+use crate::errors::ServiceError;
+
 const DEFAULT_FROM: usize = 0;
 const MAX_QUERY_LEN: usize = 100;
 const DEFAULT_QUERY_LEN: usize = 20;
@@ -12,6 +16,10 @@ pub struct ListQuery {
     len: Option<usize>,
     #[serde(default)]
     rev: bool,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    #[serde(rename = "type")]
+    list_type: Option<ListType>,
 }
 
 impl ListQuery {
@@ -25,4 +33,21 @@ impl ListQuery {
     pub fn rev(&self) -> bool {
         self.rev
     }
+
+    /// Returns whether only lists in a date range or of a type are requested.
+    pub fn is_filtered(&self) -> bool {
+        self.start_date.is_some() || self.end_date.is_some() || self.list_type.is_some()
+    }
+
+    /// Returns the requested date range, a missing bound is unlimited.
+    pub fn date_range(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>), ServiceError> {
+        match (self.start_date, self.end_date) {
+            (Some(start), Some(end)) if end < start => Err(ServiceError::InvalidDateRange),
+            range => Ok(range),
+        }
+    }
+
+    pub fn list_type(&self) -> Option<&ListType> {
+        self.list_type.as_ref()
+    }
 }
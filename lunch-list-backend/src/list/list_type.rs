@@ -1,33 +1,95 @@
-use std::{cmp::PartialEq, fmt, str::FromStr};
+use std::{fmt, fs::File, io, path::Path, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, PartialEq, Serialize)]
-#[serde(tag = "type")]
-pub enum ListType {
-    #[serde(rename = "lunch")]
-    Lunch,
-    #[serde(rename = "dinner")]
-    Dinner,
+/// Identifier of a list type, e.g. `lunch`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ListType(String);
+
+/// Display settings of a list type that can be used for new lists.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ListTypeConfig {
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    pub name: String,
+    pub color: String,
 }
 
+/// All configured list types.
+#[derive(Clone, Serialize)]
+#[serde(transparent)]
+pub struct ListTypes(Vec<ListTypeConfig>);
+
 impl FromStr for ListType {
     type Err = ();
 
+    /// Parses a list type identifier, which may only contain lowercase ASCII
+    /// letters, digits and dashes since it is used in URLs and CSS classes.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "lunch" => Ok(Self::Lunch),
-            "dinner" => Ok(Self::Dinner),
-            _ => Err(()),
+        let valid = !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if valid {
+            Ok(Self(s.to_string()))
+        } else {
+            Err(())
         }
     }
 }
 
 impl fmt::Display for ListType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Lunch => write!(f, "lunch"),
-            Self::Dinner => write!(f, "dinner"),
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ListTypes {
+    /// Reads list types from a JSON file containing an array of list types.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let types: Vec<ListTypeConfig> = serde_json::from_reader(File::open(path)?)?;
+        if types.is_empty() {
+            return Err(invalid_data("at least one list type is required".into()));
+        }
+        for config in &types {
+            if config.list_type.0.parse::<ListType>().is_err() {
+                return Err(invalid_data(format!(
+                    "invalid list type '{}', only lowercase letters, digits and dashes are allowed",
+                    config.list_type
+                )));
+            }
         }
+        Ok(Self(types))
+    }
+
+    /// Returns the display name of a list type.
+    pub fn name(&self, list_type: &ListType) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|config| &config.list_type == list_type)
+            .map(|config| config.name.as_str())
     }
+
+    /// Returns true when the list type is configured.
+    pub fn contains(&self, list_type: &ListType) -> bool {
+        self.0.iter().any(|config| &config.list_type == list_type)
+    }
+}
+
+impl Default for ListTypes {
+    fn default() -> Self {
+        let config = |list_type: &str, name: &str, color: &str| ListTypeConfig {
+            list_type: ListType(list_type.to_string()),
+            name: name.to_string(),
+            color: color.to_string(),
+        };
+        Self(vec![
+            config("lunch", "Lunch", "#a3be8c"),
+            config("dinner", "Dinner", "#d08770"),
+        ])
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
 }
@@ -1,18 +1,33 @@
 use actix_web::web;
 
+mod attendance;
 mod list_model;
+mod list_patch;
 mod list_query;
 mod list_type;
+mod rotation;
 mod routes;
 
+pub use attendance::{AttendanceRequest, Guests, Rsvp, RsvpCounts};
+pub use list_model::List;
+pub use list_type::{ListType, ListTypeConfig, ListTypes};
+
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/list")
+            .service(routes::get_list_types)
             .service(routes::get_list)
+            .service(routes::get_unanswered)
             .service(routes::get_lists)
             .service(routes::delete_list)
             .service(routes::put_list)
+            .service(routes::patch_list)
             .service(routes::add_user)
-            .service(routes::remove_user),
+            .service(routes::remove_user)
+            .service(routes::get_expenses)
+            .service(routes::post_expense)
+            .service(routes::get_cook)
+            .service(routes::put_cook)
+            .service(routes::get_shopping_list),
     );
 }
@@ -0,0 +1,94 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Body of `PUT /api/list/{id}/cook`, the suggested cook is assigned when
+/// it is missing.
+#[derive(Debug, Deserialize)]
+pub struct CookRequest {
+    pub username: String,
+}
+
+/// How often a user attended and cooked earlier lists.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CookStats {
+    pub username: String,
+    pub attended: usize,
+    pub cooked: usize,
+}
+
+impl CookStats {
+    /// Number of meals eaten per meal cooked, users with the highest score
+    /// are next in line to cook.
+    fn score(&self) -> f64 {
+        self.attended as f64 / (self.cooked + 1) as f64
+    }
+}
+
+/// Counts attendance and cooking per user over earlier lists, given as the
+/// usernames of the attendees and the cook of every list.
+pub fn count<I>(history: I) -> HashMap<String, CookStats>
+where
+    I: IntoIterator<Item = (Vec<String>, Option<String>)>,
+{
+    let mut stats: HashMap<String, CookStats> = HashMap::new();
+    for (attendees, cook) in history {
+        for username in &attendees {
+            entry(&mut stats, username).attended += 1;
+        }
+        if let Some(cook) = cook {
+            entry(&mut stats, &cook).cooked += 1;
+        }
+    }
+    stats
+}
+
+fn entry<'s>(stats: &'s mut HashMap<String, CookStats>, username: &str) -> &'s mut CookStats {
+    stats
+        .entry(username.to_string())
+        .or_insert_with(|| CookStats {
+            username: username.to_string(),
+            ..Default::default()
+        })
+}
+
+/// Orders candidates by who should cook next: the highest score first, then
+/// the fewest meals cooked, then by username.
+pub fn rank(mut candidates: Vec<CookStats>) -> Vec<CookStats> {
+    candidates.sort_by(|a, b| {
+        b.score()
+            .partial_cmp(&a.score())
+            .unwrap_or(Ordering::Equal)
+            .then(a.cooked.cmp(&b.cooked))
+            .then_with(|| a.username.cmp(&b.username))
+    });
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let stats = count(vec![
+            (names(&["Anna", "Bob", "Cleo"]), Some("Anna".to_string())),
+            (names(&["Anna", "Bob", "Cleo"]), Some("Bob".to_string())),
+            (names(&["Anna", "Cleo"]), None),
+        ]);
+        assert_eq!(stats["Cleo"].attended, 3);
+        assert_eq!(stats["Anna"].cooked, 1);
+
+        let mut candidates: Vec<_> = stats.into_values().collect();
+        candidates.push(CookStats {
+            username: "Dave".to_string(),
+            ..Default::default()
+        });
+        let ranked: Vec<_> = rank(candidates)
+            .into_iter()
+            .map(|stats| stats.username)
+            .collect();
+        assert_eq!(ranked, vec!["Cleo", "Anna", "Bob", "Dave"]);
+    }
+}
@@ -1,75 +1,172 @@
-use std::ops::DerefMut;
+use std::collections::{BTreeMap, HashMap};
 
-use actix_web::{delete, get, put, web, HttpResponse, Responder};
-use chrono::Datelike;
-use mobc_redis::{redis, redis::AsyncCommands};
+use actix_web::{delete, get, patch, post, put, web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
 use serde_json::json;
+use validator::Validate;
 
-use super::{list_model::List, list_query::ListQuery, list_type::ListType};
-use crate::{auth::Claims, errors::ServiceError, Pool};
+use super::{
+    attendance::{AttendanceRequest, Rsvp},
+    list_model::List,
+    list_patch::ListPatch,
+    list_query::ListQuery,
+    rotation::{self, CookRequest, CookStats},
+};
+use crate::{
+    auth::Claims,
+    errors::ServiceError,
+    ledger::{with_usernames, ExpenseRequest, Transaction},
+    store::{Attendance, Store},
+    user::DietSummary,
+    AppState,
+};
+
+/// Number of days before a list of which the lists count for the cook
+/// rotation.
+const COOK_HISTORY_DAYS: i64 = 365;
+
+#[get("/types")]
+async fn get_list_types(_claims: Claims, state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(json!({ "types": state.list_types }))
+}
 
 #[get("/{id}")]
 async fn get_list(
     id: web::Path<usize>,
     _claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
 ) -> Result<impl Responder, ServiceError> {
     let id = id.into_inner();
-    let mut conn = db.get().await?;
-    redis::pipe()
-        .hgetall(&format!("list:{}", id))
-        .smembers(&format!("users:{}", id))
-        .query_async(conn.deref_mut())
-        .await
-        .map(|(list, users)| {
-            if let Some(list) = List::from_hash(id, list) {
-                HttpResponse::Ok().json(list.with_users(users))
-            } else {
-                HttpResponse::NotFound().finish()
+    if let Some(list) = store.list(id).await? {
+        let users = store.attendees(id).await?;
+        let waitlist = store.waitlist(id).await?;
+        let mut guests = HashMap::new();
+        for (user_id, user_guests) in store.guests(id).await? {
+            if let Some(user) = store.user(user_id).await? {
+                guests.insert(user.username, user_guests);
+            }
+        }
+        let mut notes = HashMap::new();
+        for (user_id, note) in store.notes(id).await? {
+            if let Some(user) = store.user(user_id).await? {
+                notes.insert(user.username, note);
+            }
+        }
+        let mut maybe = Vec::new();
+        let mut no = Vec::new();
+        for (user_id, answer) in store.answers(id).await? {
+            if let Some(user) = store.user(user_id).await? {
+                match answer {
+                    Rsvp::Maybe => maybe.push(user.username),
+                    Rsvp::No => no.push(user.username),
+                    Rsvp::Yes => {}
+                }
             }
+        }
+        maybe.sort();
+        no.sort();
+        let mut diets = Vec::new();
+        for user_id in store.attendee_ids(id).await? {
+            if let Some(user) = store.user(user_id).await? {
+                diets.push(user.diet);
+            }
+        }
+        Ok(HttpResponse::Ok().json(
+            list.with_users(users)
+                .with_waitlist(waitlist)
+                .with_guests(guests)
+                .with_notes(notes)
+                .with_answers(maybe, no)
+                .with_diet(DietSummary::new(&diets)),
+        ))
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+/// Returns the usernames of everyone who has not answered yet, neither
+/// joined, waitlisted, declined nor answered maybe.
+#[get("/{id}/unanswered")]
+async fn get_unanswered(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let id = id.into_inner();
+    if store.list(id).await?.is_none() {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    let attendees = store.attendee_ids(id).await?;
+    let waitlist = store.waitlist_ids(id).await?;
+    let answers = store.answers(id).await?;
+    let mut users: Vec<String> = store
+        .users()
+        .await?
+        .into_iter()
+        .filter(|user| {
+            !attendees.contains(&user.id)
+                && !waitlist.contains(&user.id)
+                && !answers.contains_key(&user.id)
         })
-        .map_err(ServiceError::from)
+        .map(|user| user.username)
+        .collect();
+    users.sort();
+    Ok(HttpResponse::Ok().json(json!({ "users": users })))
 }
 
 #[get("")]
 async fn get_lists(
     query: web::Query<ListQuery>,
     _claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
 ) -> Result<impl Responder, ServiceError> {
-    let mut conn = db.get().await?;
     let (start, stop) = query.to_range();
-    let ids: Vec<usize> = if query.rev() {
-        conn.zrange("dates", start as isize, stop as isize).await?
+    let ids = if query.is_filtered() {
+        filtered_list_ids(&query, store.as_ref()).await?
     } else {
-        conn.zrevrange("dates", start as isize, stop as isize)
-            .await?
+        store.list_ids(start, stop, query.rev()).await?
     };
 
     let mut lists = Vec::new();
     for id in ids {
-        let list = conn.hgetall(&format!("list:{}", id)).await?;
-        let card = conn.scard(&format!("users:{}", id)).await?;
-        lists.push(List::from_hash(id, list).unwrap().with_size(card))
+        let list = store.list(id).await?.ok_or(ServiceError::InternalError)?;
+        let attendees = store.attendee_ids(id).await?;
+        let guests = store.guests(id).await?;
+        let answers = store.answers(id).await?;
+        lists.push(list.with_counts(&attendees, &guests, &answers))
     }
     Ok(HttpResponse::Ok().json(json!({ "lists": lists })))
 }
 
+/// Returns the page of list ids matching the date range and type of the
+/// query, newest first unless reversed.
+async fn filtered_list_ids(
+    query: &ListQuery,
+    store: &dyn Store,
+) -> Result<Vec<usize>, ServiceError> {
+    let (start_date, end_date) = query.date_range()?;
+    let mut ids = store
+        .list_ids_between(start_date, end_date, query.list_type())
+        .await?;
+    if !query.rev() {
+        ids.reverse();
+    }
+    let (start, stop) = query.to_range();
+    Ok(ids
+        .into_iter()
+        .skip(start)
+        .take((stop + 1).saturating_sub(start))
+        .collect())
+}
+
 #[delete("/{id}")]
 async fn delete_list(
     id: web::Path<usize>,
     _claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
 ) -> Result<impl Responder, ServiceError> {
-    let mut conn = db.get().await?;
-    let id = id.into_inner();
-    let (_, _, b): (bool, bool, bool) = redis::pipe()
-        .zrem("dates", id)
-        .del(&format!("users:{}", id))
-        .del(&format!("list:{}", id))
-        .query_async(conn.deref_mut())
-        .await?;
-    if b {
+    if store.delete_list(id.into_inner()).await? {
         Ok(HttpResponse::NoContent())
     } else {
         Ok(HttpResponse::NotFound())
@@ -80,81 +177,100 @@ async fn delete_list(
 async fn put_list(
     list: web::Json<List>,
     _claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
 ) -> Result<impl Responder, ServiceError> {
-    let days = list.date.num_days_from_ce();
-    let mut conn = db.get().await?;
-    let list_id: Vec<usize> = conn.zrangebyscore("dates", days, days).await?;
-    let id: Option<usize> = match list_id.as_slice() {
-        [id] => {
-            let list_type = conn
-                .hget::<&str, &str, String>(&format!("list:{}", id), "type")
-                .await?
-                .parse::<ListType>()
-                .unwrap();
-            if list_type != list.list_type {
-                let id: usize = conn.incr("next_list_id", 1_usize).await?;
-                redis::pipe()
-                    .hset_multiple(
-                        &format!("list:{}", id),
-                        &[
-                            ("type", list.list_type.to_string()),
-                            ("date", list.date.to_string()),
-                        ],
-                    )
-                    .zadd("dates", id, days)
-                    .query_async(conn.deref_mut())
-                    .await?;
-                Some(id)
-            } else {
-                None
-            }
-        }
-        [] => {
-            let id: usize = conn.incr("next_list_id", 1_usize).await?;
-            redis::pipe()
-                .hset_multiple(
-                    &format!("list:{}", id),
-                    &[
-                        ("type", list.list_type.to_string()),
-                        ("date", list.date.to_string()),
-                    ],
-                )
-                .zadd("dates", id, days)
-                .query_async(conn.deref_mut())
-                .await?;
-            Some(id)
-        }
-        _ => None,
+    list.validate()?;
+    if !state.list_types.contains(&list.list_type) {
+        return Err(ServiceError::UnknownListType {
+            list_type: list.list_type.to_string(),
+        });
+    }
+
+    check_recipe(store.as_ref(), &list).await?;
+    if let Some(holiday) = store.holiday(list.date).await? {
+        return Err(ServiceError::DateClosed {
+            date: holiday.date,
+            name: holiday.name,
+        });
+    }
+
+    let id = store.create_list(&list).await?;
+    Ok(HttpResponse::Created().json(json!({ "id": id })))
+}
+
+#[patch("/{id}")]
+async fn patch_list(
+    id: web::Path<usize>,
+    patch: web::Json<ListPatch>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, ServiceError> {
+    let mut list = match store.list(id.into_inner()).await? {
+        Some(list) => list,
+        None => return Ok(HttpResponse::NotFound().finish()),
     };
+    let (date, list_type, recipe) = (list.date, list.list_type.clone(), list.recipe);
+    patch.into_inner().apply(&mut list);
+    list.validate()?;
+    // Lists of a type that was removed from the config can still be edited.
+    if list.list_type != list_type && !state.list_types.contains(&list.list_type) {
+        return Err(ServiceError::UnknownListType {
+            list_type: list.list_type.to_string(),
+        });
+    }
+    if list.recipe != recipe {
+        check_recipe(store.as_ref(), &list).await?;
+    }
+    if list.date != date {
+        if let Some(holiday) = store.holiday(list.date).await? {
+            return Err(ServiceError::DateClosed {
+                date: holiday.date,
+                name: holiday.name,
+            });
+        }
+    }
 
-    if let Some(id) = id {
-        Ok(HttpResponse::Created().json(json!({ "id": id })))
+    if store.update_list(&list).await? {
+        Ok(HttpResponse::Ok().json(list))
     } else {
-        Ok(HttpResponse::NoContent().finish())
+        Ok(HttpResponse::NotFound().finish())
     }
 }
 
 #[put("/{id}/user")]
 async fn add_user(
     id: web::Path<usize>,
+    attendance: Option<web::Json<AttendanceRequest>>,
     claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
 ) -> Result<impl Responder, ServiceError> {
     let id = id.into_inner();
-    let mut conn = db.get().await?;
+    let attendance = attendance.map(web::Json::into_inner).unwrap_or_default();
+    attendance.validate()?;
 
-    let exists = conn.exists(&format!("list:{}", id)).await?;
-
-    if exists {
-        let added = conn.sadd(&format!("users:{}", id), claims.name).await?;
-        if added {
-            Ok(HttpResponse::Created())
-        } else {
+    match store.list(id).await? {
+        Some(list) if list.is_closed(Utc::now()) => Err(ServiceError::DeadlinePassed),
+        Some(_) if attendance.rsvp() == Rsvp::Yes => {
+            let joined = store
+                .add_attendee(id, claims.sub, &attendance.guests(), attendance.note())
+                .await?;
+            store.set_answer(id, claims.sub, None).await?;
+            match joined {
+                Attendance::Joined => Ok(HttpResponse::Created()),
+                Attendance::Waitlisted => Ok(HttpResponse::Accepted()),
+                Attendance::Unchanged => Ok(HttpResponse::NoContent()),
+            }
+        }
+        Some(_) => {
+            store.remove_attendee(id, claims.sub).await?;
+            store
+                .set_answer(id, claims.sub, Some(attendance.rsvp()))
+                .await?;
             Ok(HttpResponse::NoContent())
         }
-    } else {
-        Ok(HttpResponse::NotFound())
+        None => Ok(HttpResponse::NotFound()),
     }
 }
 
@@ -162,17 +278,201 @@ async fn add_user(
 async fn remove_user(
     id: web::Path<usize>,
     claims: Claims,
-    db: web::Data<Pool>,
+    store: web::Data<dyn Store>,
 ) -> Result<impl Responder, ServiceError> {
     let id = id.into_inner();
-    let mut conn = db.get().await?;
 
-    let exists = conn.exists(&format!("list:{}", id)).await?;
+    match store.list(id).await? {
+        Some(list) if list.is_closed(Utc::now()) => Err(ServiceError::DeadlinePassed),
+        Some(_) => {
+            store.remove_attendee(id, claims.sub).await?;
+            store.set_answer(id, claims.sub, None).await?;
+            Ok(HttpResponse::NoContent())
+        }
+        None => Ok(HttpResponse::NotFound()),
+    }
+}
 
-    if exists {
-        conn.srem(&format!("users:{}", id), claims.sub).await?;
-        Ok(HttpResponse::NoContent())
+#[get("/{id}/expense")]
+async fn get_expenses(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let id = id.into_inner();
+    let expenses = store
+        .transactions()
+        .await?
+        .into_iter()
+        .filter(|transaction| transaction.list_id == Some(id))
+        .collect();
+    let expenses = with_usernames(store.as_ref(), expenses).await?;
+    Ok(HttpResponse::Ok().json(json!({ "expenses": expenses })))
+}
+
+/// Records an expense paid by the caller, split evenly across the current
+/// attendees of the list and their guests.
+#[post("/{id}/expense")]
+async fn post_expense(
+    id: web::Path<usize>,
+    expense: web::Json<ExpenseRequest>,
+    claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let id = id.into_inner();
+    expense.validate()?;
+    if store.list(id).await?.is_none() {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    let guests = store.guests(id).await?;
+    let shares: BTreeMap<usize, usize> = store
+        .attendee_ids(id)
+        .await?
+        .into_iter()
+        .map(|user_id| (user_id, 1 + guests.get(&user_id).map_or(0, |g| g.count)))
+        .collect();
+    let expense = expense.into_inner();
+    let mut transaction =
+        Transaction::expense(id, claims.sub, expense.amount, expense.description, &shares)
+            .ok_or(ServiceError::NothingToSplit)?;
+    transaction.id = store.add_transaction(&transaction).await?;
+    let view = with_usernames(store.as_ref(), vec![transaction]).await?;
+    Ok(HttpResponse::Created().json(&view[0]))
+}
+
+/// Returns the cook of a list and its attendees ordered by who should cook
+/// next, based on how often they attended and cooked earlier lists.
+#[get("/{id}/cook")]
+async fn get_cook(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let list = match store.list(id.into_inner()).await? {
+        Some(list) => list,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+
+    let candidates = cook_candidates(store.as_ref(), &list).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "cook": list.cook,
+        "suggestion": candidates.first().map(|stats| &stats.username),
+        "candidates": candidates,
+    })))
+}
+
+/// Assigns a user as cook of a list, or the suggested cook when no user is
+/// given.
+#[put("/{id}/cook")]
+async fn put_cook(
+    id: web::Path<usize>,
+    cook: Option<web::Json<CookRequest>>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let mut list = match store.list(id.into_inner()).await? {
+        Some(list) => list,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+
+    let username = match cook {
+        Some(cook) => {
+            let user_id = store.user_id(cook.username.trim()).await?;
+            let user = match user_id {
+                Some(user_id) => store.user(user_id).await?,
+                None => None,
+            };
+            user.ok_or_else(|| ServiceError::UnknownUser {
+                username: cook.username.clone(),
+            })?
+            .username
+        }
+        None => {
+            cook_candidates(store.as_ref(), &list)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or(ServiceError::NoCookCandidates)?
+                .username
+        }
+    };
+    list.cook = Some(username);
+
+    if store.update_list(&list).await? {
+        Ok(HttpResponse::Ok().json(list))
     } else {
-        Ok(HttpResponse::NotFound())
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+/// Returns the ingredients of the recipe of a list, scaled by the number of
+/// attendees and their guests.
+#[get("/{id}/shopping")]
+async fn get_shopping_list(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let id = id.into_inner();
+    let list = match store.list(id).await? {
+        Some(list) => list,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+    let recipe = match list.recipe {
+        Some(recipe) => store.recipe(recipe).await?,
+        None => None,
+    }
+    .ok_or(ServiceError::NoRecipe)?;
+
+    let attendees = store.attendee_ids(id).await?;
+    let guests = store.guests(id).await?;
+    let headcount = attendees.len()
+        + attendees
+            .iter()
+            .filter_map(|user_id| guests.get(user_id))
+            .map(|guests| guests.count)
+            .sum::<usize>();
+    Ok(HttpResponse::Ok().json(json!({
+        "recipe": recipe.name,
+        "headcount": headcount,
+        "items": recipe.shopping_list(headcount),
+    })))
+}
+
+/// Fails when the list references a recipe that does not exist.
+async fn check_recipe(store: &dyn Store, list: &List) -> Result<(), ServiceError> {
+    match list.recipe {
+        Some(id) if store.recipe(id).await?.is_none() => Err(ServiceError::UnknownRecipe { id }),
+        _ => Ok(()),
     }
 }
+
+/// Returns the attendees of a list ranked by who should cook next, counting
+/// the lists of the `COOK_HISTORY_DAYS` days before its date. The cook of a
+/// list is free text, only cooks that exactly match a username count.
+async fn cook_candidates(store: &dyn Store, list: &List) -> Result<Vec<CookStats>, ServiceError> {
+    let start = list.date - Duration::days(COOK_HISTORY_DAYS);
+    let end = list.date - Duration::days(1);
+    let mut history = Vec::new();
+    for id in store.list_ids_between(Some(start), Some(end), None).await? {
+        if let Some(earlier) = store.list(id).await? {
+            let attendees = store.attendees(id).await?.into_iter().collect();
+            history.push((attendees, earlier.cook));
+        }
+    }
+
+    let mut stats = rotation::count(history);
+    let candidates = store
+        .attendees(list.id)
+        .await?
+        .into_iter()
+        .map(|username| {
+            stats.remove(&username).unwrap_or(CookStats {
+                username,
+                ..Default::default()
+            })
+        })
+        .collect();
+    Ok(rotation::rank(candidates))
+}
@@ -1,11 +1,19 @@
-use std::io;
+use std::{io, path::PathBuf, str::FromStr, sync::Arc};
 
 use actix_files::{Files, NamedFile};
 use actix_web::{middleware, web, App, FromRequest, HttpServer};
-use clap::Clap;
+use clap::{ArgSettings, Clap};
 use mobc_redis::{redis, RedisConnectionManager};
 
-use lunch_list_backend::{auth, list, not_found, user, AppState};
+use commands::Command;
+use lunch_list_backend::{
+    auth, config,
+    list::ListTypes,
+    store::{MemoryStore, RedisStore, SqliteStore, Store},
+    template, AppState,
+};
+
+mod commands;
 
 const ASSETS_DIR: &str = "dist";
 const INDEX_HTML: &str = "index.html";
@@ -15,7 +23,8 @@ const INDEX_HTML: &str = "index.html";
     version = env!("CARGO_PKG_VERSION"),
     author = env!("CARGO_PKG_AUTHORS"),
     about = env!("CARGO_PKG_DESCRIPTION"),
-    setting = clap::AppSettings::ColoredHelp
+    setting = clap::AppSettings::ColoredHelp,
+    setting = clap::AppSettings::SubcommandsNegateReqs
 )]
 struct Opts {
     /// Address to bind the server to
@@ -26,17 +35,66 @@ struct Opts {
     #[clap(short, long, env = "LUNCH_LIST_PORT", default_value = "8080")]
     port: u16,
 
+    /// Storage backend to use, defaults to sqlite when a database is given and
+    /// to redis otherwise
+    #[clap(
+        long,
+        global = true,
+        env = "LUNCH_LIST_STORAGE",
+        possible_values = &["redis", "memory", "sqlite"]
+    )]
+    storage: Option<Storage>,
+
+    /// SQLite database URL, defaults to sqlite://lunch-list.db
+    #[clap(long, global = true, env = "LUNCH_LIST_DATABASE")]
+    database: Option<String>,
+
     /// Redis hostname to connect to
-    #[clap(long, env = "LUNCH_LIST_REDIS", default_value = "localhost")]
+    #[clap(
+        long,
+        global = true,
+        env = "LUNCH_LIST_REDIS",
+        default_value = "localhost"
+    )]
     redis_host: String,
 
     /// Secret used for encoding and decoding JWTs
-    #[clap(long, env = "LUNCH_LIST_TOKEN_SECRET")]
-    token_secret: String,
+    #[clap(long, env = "LUNCH_LIST_TOKEN_SECRET", setting = ArgSettings::Required)]
+    token_secret: Option<String>,
 
     /// Secret used when creating a new user
-    #[clap(long, env = "LUNCH_LIST_SIGNUP_SECRET")]
-    signup_secret: String,
+    #[clap(long, env = "LUNCH_LIST_SIGNUP_SECRET", setting = ArgSettings::Required)]
+    signup_secret: Option<String>,
+
+    /// JSON file with the list types that can be used, defaults to lunch and dinner
+    #[clap(long, global = true, env = "LUNCH_LIST_TYPES")]
+    list_types: Option<PathBuf>,
+
+    /// Number of days ahead for which lists are created from templates, 0 disables it
+    #[clap(long, env = "LUNCH_LIST_GENERATE_DAYS", default_value = "14")]
+    generate_days: u32,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+enum Storage {
+    Redis,
+    Memory,
+    Sqlite,
+}
+
+impl FromStr for Storage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "redis" => Ok(Self::Redis),
+            "memory" => Ok(Self::Memory),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(format!("unknown storage backend '{}'", s)),
+        }
+    }
 }
 
 async fn serve_index_html() -> Result<NamedFile, std::io::Error> {
@@ -47,33 +105,46 @@ async fn serve_index_html() -> Result<NamedFile, std::io::Error> {
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    let opts = Opts::parse();
+    let mut opts = Opts::parse();
 
     std::env::set_var("RUST_LOG", "debug");
     env_logger::init();
 
-    let pool = build_pool(&opts)?;
+    let store = build_store(&opts)?;
+    let list_types = match &opts.list_types {
+        Some(path) => ListTypes::from_file(path)?,
+        None => ListTypes::default(),
+    };
+
+    match opts.command.take() {
+        Some(command) => commands::run(command, store, &list_types).await,
+        None => serve(opts, store, list_types).await,
+    }
+}
+
+async fn serve(opts: Opts, store: Arc<dyn Store>, list_types: ListTypes) -> std::io::Result<()> {
+    commands::check_migrations(store.as_ref()).await?;
+
+    if opts.generate_days > 0 {
+        actix_rt::spawn(template::generate_lists(store.clone(), opts.generate_days));
+    }
 
-    let token_secret = opts.token_secret;
-    let signup_secret = opts.signup_secret;
+    // Both secrets are required by clap when no subcommand is given.
+    let token_secret = opts.token_secret.expect("missing token secret");
+    let signup_secret = opts.signup_secret.expect("missing signup secret");
 
     HttpServer::new(move || {
         App::new()
-            .data(pool.clone())
+            .app_data(web::Data::from(store.clone()))
             .data(AppState {
                 token_secret: token_secret.clone(),
                 signup_secret: signup_secret.clone(),
+                list_types: list_types.clone(),
             })
             .app_data(auth::Claims::configure(|cfg| cfg.secret(&token_secret)))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
-            .service(
-                web::scope("/api")
-                    .configure(auth::config)
-                    .configure(list::config)
-                    .configure(user::config)
-                    .default_service(web::route().to(not_found)),
-            )
+            .configure(config)
             .service(Files::new("/", ASSETS_DIR).index_file(INDEX_HTML))
             .default_service(web::get().to(serve_index_html))
     })
@@ -82,9 +153,41 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
+fn build_store(opts: &Opts) -> std::io::Result<Arc<dyn Store>> {
+    let storage = match (&opts.storage, &opts.database) {
+        (Some(Storage::Redis), Some(_)) | (Some(Storage::Memory), Some(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "database can only be used with the sqlite storage backend",
+            ))
+        }
+        (Some(storage), _) => storage,
+        (None, Some(_)) => &Storage::Sqlite,
+        (None, None) => &Storage::Redis,
+    };
+    match storage {
+        Storage::Redis => Ok(Arc::new(RedisStore::new(build_pool(opts)?))),
+        Storage::Memory => {
+            log::warn!("Using in-memory storage, all data is lost on exit");
+            Ok(Arc::new(MemoryStore::new()))
+        }
+        Storage::Sqlite => {
+            let database = opts.database.as_deref().unwrap_or("sqlite://lunch-list.db");
+            let path = database.strip_prefix("sqlite://").ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "database must be a 'sqlite://' URL",
+                )
+            })?;
+            let store = SqliteStore::open(path).map_err(io::Error::other)?;
+            Ok(Arc::new(store))
+        }
+    }
+}
+
 fn build_pool(opts: &Opts) -> std::io::Result<mobc::Pool<RedisConnectionManager>> {
-    let client = redis::Client::open(format!("redis://{}/", opts.redis_host))
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let client =
+        redis::Client::open(format!("redis://{}/", opts.redis_host)).map_err(io::Error::other)?;
     let manager = RedisConnectionManager::new(client);
     Ok(mobc::Pool::new(manager))
 }
@@ -0,0 +1,16 @@
+use actix_web::web;
+
+mod recipe_model;
+mod routes;
+
+pub use recipe_model::{Ingredient, Recipe};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/recipe")
+            .service(routes::get_recipes)
+            .service(routes::get_recipe)
+            .service(routes::put_recipe)
+            .service(routes::delete_recipe),
+    );
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use validator_derive::Validate;
+
+/// Recipe with the ingredients needed for a single person.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Validate)]
+pub struct Recipe {
+    #[serde(skip_deserializing)]
+    pub id: usize,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    #[validate(length(min = 1, message = "At least one ingredient is required"))]
+    pub ingredients: Vec<Ingredient>,
+}
+
+/// Quantity of an ingredient, e.g. 150 g of rice.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Validate)]
+pub struct Ingredient {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    #[validate(range(
+        min = 0.0,
+        max = 1_000_000.0,
+        message = "Quantity must be between 0 and 1000000"
+    ))]
+    pub quantity: f64,
+    #[serde(default)]
+    #[validate(length(max = 20, message = "Unit cannot be longer than 20 characters"))]
+    pub unit: String,
+}
+
+impl Recipe {
+    /// Returns the ingredients needed for `headcount` people. Ingredients
+    /// with the same name and unit are combined.
+    pub fn shopping_list(&self, headcount: usize) -> Vec<Ingredient> {
+        let mut items: Vec<Ingredient> = Vec::new();
+        for ingredient in &self.ingredients {
+            let quantity = ingredient.quantity * headcount as f64;
+            let existing = items
+                .iter_mut()
+                .find(|item| item.name == ingredient.name && item.unit == ingredient.unit);
+            match existing {
+                Some(item) => item.quantity += quantity,
+                None => items.push(Ingredient {
+                    quantity,
+                    ..ingredient.clone()
+                }),
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shopping_list() {
+        let ingredient = |name: &str, quantity, unit: &str| Ingredient {
+            name: name.to_string(),
+            quantity,
+            unit: unit.to_string(),
+        };
+        let recipe = Recipe {
+            id: 1,
+            name: "Risotto".to_string(),
+            ingredients: vec![
+                ingredient("rice", 80.0, "g"),
+                ingredient("onion", 0.25, ""),
+                ingredient("rice", 20.0, "g"),
+            ],
+        };
+        assert_eq!(
+            recipe.shopping_list(6),
+            vec![ingredient("rice", 600.0, "g"), ingredient("onion", 1.5, "")]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use actix_web::{delete, get, put, web, HttpResponse, Responder};
+use serde_json::json;
+use validator::Validate;
+
+use super::recipe_model::Recipe;
+use crate::{auth::Claims, errors::ServiceError, store::Store};
+
+#[get("")]
+async fn get_recipes(
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let recipes = store.recipes().await?;
+    Ok(HttpResponse::Ok().json(json!({ "recipes": recipes })))
+}
+
+#[get("/{id}")]
+async fn get_recipe(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    match store.recipe(id.into_inner()).await? {
+        Some(recipe) => Ok(HttpResponse::Ok().json(recipe)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+#[put("")]
+async fn put_recipe(
+    recipe: web::Json<Recipe>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    recipe.validate()?;
+    for ingredient in &recipe.ingredients {
+        ingredient.validate()?;
+    }
+
+    let id = store.create_recipe(&recipe).await?;
+    Ok(HttpResponse::Created().json(json!({ "id": id })))
+}
+
+#[delete("/{id}")]
+async fn delete_recipe(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    if store.delete_recipe(id.into_inner()).await? {
+        Ok(HttpResponse::NoContent())
+    } else {
+        Ok(HttpResponse::NotFound())
+    }
+}
@@ -0,0 +1,131 @@
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+use super::report_model::{Report, ReportFormat};
+use crate::errors::ServiceError;
+
+const DAY_HEADER: [&str; 5] = ["date", "type", "attendees", "guests", "total"];
+const USER_HEADER: [&str; 3] = ["username", "lists", "guests"];
+
+impl Report {
+    /// Writes the report in the given format.
+    pub fn write(&self, format: ReportFormat) -> Result<Vec<u8>, ServiceError> {
+        match format {
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Xlsx => self.to_xlsx().map_err(|e| {
+                log::error!("Failed to write XLSX report: {}", e);
+                ServiceError::InternalError
+            }),
+        }
+    }
+
+    /// Writes the per-day counts followed by an empty line and the per-user
+    /// totals, each with a header row.
+    fn to_csv(&self) -> Result<Vec<u8>, ServiceError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(DAY_HEADER).map_err(csv_error)?;
+        for day in &self.days {
+            writer
+                .write_record(&[
+                    day.date.to_string(),
+                    day.list_type.to_string(),
+                    day.attendees.to_string(),
+                    day.guests.to_string(),
+                    day.total().to_string(),
+                ])
+                .map_err(csv_error)?;
+        }
+        let mut csv = writer
+            .into_inner()
+            .map_err(|_| ServiceError::InternalError)?;
+        csv.push(b'\n');
+
+        // The user table has fewer columns, so it needs its own writer.
+        let mut writer = csv::Writer::from_writer(csv);
+        writer.write_record(USER_HEADER).map_err(csv_error)?;
+        for user in &self.users {
+            writer
+                .write_record(&[
+                    user.username.clone(),
+                    user.lists.to_string(),
+                    user.guests.to_string(),
+                ])
+                .map_err(csv_error)?;
+        }
+        writer.into_inner().map_err(|_| ServiceError::InternalError)
+    }
+
+    /// Writes a workbook with a "Days" and a "Users" worksheet.
+    fn to_xlsx(&self) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+        let date_format = Format::new().set_num_format("yyyy-mm-dd");
+
+        let days = workbook.add_worksheet().set_name("Days")?;
+        days.write_row_with_format(0, 0, DAY_HEADER, &bold)?;
+        for (row, day) in (1..).zip(&self.days) {
+            days.write_date_with_format(row, 0, day.date, &date_format)?;
+            days.write_string(row, 1, day.list_type.to_string())?;
+            days.write_number(row, 2, day.attendees as f64)?;
+            days.write_number(row, 3, day.guests as f64)?;
+            days.write_number(row, 4, day.total() as f64)?;
+        }
+        days.set_column_width(0, 12)?;
+
+        let users = workbook.add_worksheet().set_name("Users")?;
+        users.write_row_with_format(0, 0, USER_HEADER, &bold)?;
+        for (row, user) in (1..).zip(&self.users) {
+            users.write_string(row, 0, &user.username)?;
+            users.write_number(row, 1, user.lists as f64)?;
+            users.write_number(row, 2, user.guests as f64)?;
+        }
+        users.set_column_width(0, 20)?;
+
+        workbook.save_to_buffer()
+    }
+}
+
+fn csv_error(e: csv::Error) -> ServiceError {
+    log::error!("Failed to write CSV report: {}", e);
+    ServiceError::InternalError
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::report::{DayCount, UserTotal};
+
+    #[test]
+    fn test_write_report() {
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let report = Report {
+            start_date: date,
+            end_date: date,
+            days: vec![DayCount {
+                date,
+                list_type: "dinner".parse().unwrap(),
+                attendees: 2,
+                guests: 1,
+            }],
+            users: vec![UserTotal {
+                username: "Sir, User".to_string(),
+                lists: 1,
+                guests: 1,
+            }],
+        };
+
+        let csv = String::from_utf8(report.write(ReportFormat::Csv).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "date,type,attendees,guests,total\n\
+             2020-12-24,dinner,2,1,3\n\
+             \n\
+             username,lists,guests\n\
+             \"Sir, User\",1,1\n"
+        );
+
+        let xlsx = report.write(ReportFormat::Xlsx).unwrap();
+        assert!(xlsx.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,11 @@
+use actix_web::web;
+
+mod export;
+mod report_model;
+mod routes;
+
+pub use report_model::{DayCount, Report, ReportFormat, UserTotal};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/report").service(routes::get_report));
+}
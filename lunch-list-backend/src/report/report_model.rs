@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::ServiceError, list::ListType, store::Store};
+
+/// File format of a report.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+/// Number of attendees of a single list.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub list_type: ListType,
+    pub attendees: usize,
+    pub guests: usize,
+}
+
+/// Number of lists a user attended and the guests they brought.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct UserTotal {
+    pub username: String,
+    pub lists: usize,
+    pub guests: usize,
+}
+
+/// Attendance over a range of dates, used by facilities to plan ahead.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Lists ordered by date.
+    pub days: Vec<DayCount>,
+    /// Users ordered by username.
+    pub users: Vec<UserTotal>,
+}
+
+impl Report {
+    /// Collects the attendance of all lists from `start_date` up to and
+    /// including `end_date`, optionally only of the given type.
+    pub async fn build(
+        store: &dyn Store,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        list_type: Option<&ListType>,
+    ) -> Result<Self, ServiceError> {
+        if end_date < start_date {
+            return Err(ServiceError::InvalidDateRange);
+        }
+
+        let mut days = Vec::new();
+        let mut totals: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for id in store
+            .list_ids_between(Some(start_date), Some(end_date), list_type)
+            .await?
+        {
+            let list = match store.list(id).await? {
+                Some(list) => list,
+                None => continue,
+            };
+
+            let attendees = store.attendee_ids(id).await?;
+            let guests = store.guests(id).await?;
+            let mut guest_count = 0;
+            for user_id in &attendees {
+                let count = guests.get(user_id).map_or(0, |guests| guests.count);
+                let total = totals.entry(*user_id).or_default();
+                total.0 += 1;
+                total.1 += count;
+                guest_count += count;
+            }
+            days.push(DayCount {
+                date: list.date,
+                list_type: list.list_type,
+                attendees: attendees.len(),
+                guests: guest_count,
+            });
+        }
+
+        let mut users = Vec::new();
+        for (user_id, (lists, guests)) in totals {
+            if let Some(user) = store.user(user_id).await? {
+                users.push(UserTotal {
+                    username: user.username,
+                    lists,
+                    guests,
+                });
+            }
+        }
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+
+        Ok(Self {
+            start_date,
+            end_date,
+            days,
+            users,
+        })
+    }
+}
+
+impl DayCount {
+    /// Returns the number of attendees including their guests.
+    pub fn total(&self) -> usize {
+        self.attendees + self.guests
+    }
+}
+
+impl ReportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Xlsx => write!(f, "xlsx"),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "xlsx" => Ok(Self::Xlsx),
+            _ => Err(format!("unknown report format '{}', use csv or xlsx", s)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use super::report_model::{Report, ReportFormat};
+use crate::{auth::Claims, errors::ServiceError, list::ListType, store::Store};
+
+#[derive(Debug, Deserialize)]
+struct ReportQuery {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    #[serde(default, rename = "type")]
+    list_type: Option<ListType>,
+    #[serde(default)]
+    format: ReportFormat,
+}
+
+/// Returns the attendance per list and per user over a range of dates as a
+/// CSV or XLSX file.
+#[get("")]
+async fn get_report(
+    query: web::Query<ReportQuery>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let query = query.into_inner();
+    let report = Report::build(
+        store.as_ref(),
+        query.start_date,
+        query.end_date,
+        query.list_type.as_ref(),
+    )
+    .await?;
+    let filename = format!(
+        "report-{}-{}.{}",
+        query.start_date, query.end_date, query.format
+    );
+    Ok(HttpResponse::Ok()
+        .content_type(query.format.content_type())
+        .header(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(report.write(query.format)?))
+}
@@ -0,0 +1,8 @@
+use actix_web::web;
+
+mod routes;
+mod stats_model;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/stats").service(routes::get_stats));
+}
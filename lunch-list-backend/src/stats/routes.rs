@@ -0,0 +1,33 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{Duration, Local, NaiveDate};
+use serde::Deserialize;
+
+use super::stats_model::Stats;
+use crate::{auth::Claims, errors::ServiceError, report::Report, store::Store};
+
+/// Number of days up to today that are included by default.
+const DEFAULT_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize)]
+struct StatsQuery {
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+}
+
+/// Returns attendance figures over a range of dates, the last 90 days by
+/// default.
+#[get("")]
+async fn get_stats(
+    query: web::Query<StatsQuery>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let end_date = query
+        .end_date
+        .unwrap_or_else(|| Local::now().naive_local().date());
+    let start_date = query
+        .start_date
+        .unwrap_or(end_date - Duration::days(DEFAULT_DAYS - 1));
+    let report = Report::build(store.as_ref(), start_date, end_date, None).await?;
+    Ok(HttpResponse::Ok().json(Stats::from_report(report)))
+}
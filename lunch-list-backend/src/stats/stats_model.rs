@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::{
+    list::ListType,
+    report::{Report, UserTotal},
+};
+
+/// Number of users in the top attendees.
+const TOP_ATTENDEES: usize = 10;
+
+/// Attendance figures over a range of dates. All headcounts include guests.
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Average headcount of the lists on each weekday, Monday first.
+    pub weekdays: Vec<WeekdayAverage>,
+    /// Average headcount of the lists of each type.
+    pub types: Vec<TypeAverage>,
+    /// Users that attended the most lists.
+    pub top_attendees: Vec<UserTotal>,
+    /// Headcount of all lists per date, ordered by date.
+    pub daily: Vec<DailyHeadcount>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WeekdayAverage {
+    pub weekday: Weekday,
+    pub lists: usize,
+    pub average: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TypeAverage {
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    pub lists: usize,
+    pub average: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DailyHeadcount {
+    pub date: NaiveDate,
+    pub headcount: usize,
+}
+
+impl Stats {
+    pub fn from_report(report: Report) -> Self {
+        let mut weekdays: BTreeMap<u32, (Weekday, usize, usize)> = BTreeMap::new();
+        let mut types: BTreeMap<String, (ListType, usize, usize)> = BTreeMap::new();
+        let mut daily: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for day in &report.days {
+            let weekday = day.date.weekday();
+            let entry = weekdays
+                .entry(weekday.num_days_from_monday())
+                .or_insert((weekday, 0, 0));
+            entry.1 += 1;
+            entry.2 += day.total();
+
+            let entry = types
+                .entry(day.list_type.to_string())
+                .or_insert_with(|| (day.list_type.clone(), 0, 0));
+            entry.1 += 1;
+            entry.2 += day.total();
+
+            *daily.entry(day.date).or_default() += day.total();
+        }
+
+        let mut top_attendees = report.users;
+        top_attendees.sort_by(|a, b| b.lists.cmp(&a.lists).then(a.username.cmp(&b.username)));
+        top_attendees.truncate(TOP_ATTENDEES);
+
+        Self {
+            start_date: report.start_date,
+            end_date: report.end_date,
+            weekdays: weekdays
+                .into_values()
+                .map(|(weekday, lists, total)| WeekdayAverage {
+                    weekday,
+                    lists,
+                    average: average(total, lists),
+                })
+                .collect(),
+            types: types
+                .into_values()
+                .map(|(list_type, lists, total)| TypeAverage {
+                    list_type,
+                    lists,
+                    average: average(total, lists),
+                })
+                .collect(),
+            top_attendees,
+            daily: daily
+                .into_iter()
+                .map(|(date, headcount)| DailyHeadcount { date, headcount })
+                .collect(),
+        }
+    }
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    total as f64 / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::DayCount;
+
+    #[test]
+    fn test_from_report() {
+        let day = |d, list_type: &str, attendees| DayCount {
+            date: NaiveDate::from_ymd_opt(2020, 12, d).unwrap(),
+            list_type: list_type.parse().unwrap(),
+            attendees,
+            guests: 0,
+        };
+        let user = |username: &str, lists| UserTotal {
+            username: username.to_string(),
+            lists,
+            guests: 0,
+        };
+        let report = Report {
+            start_date: NaiveDate::from_ymd_opt(2020, 12, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+            // Tuesdays and a Wednesday
+            days: vec![
+                day(1, "lunch", 4),
+                day(1, "dinner", 2),
+                day(2, "lunch", 3),
+                day(8, "lunch", 6),
+            ],
+            users: vec![user("Anna", 2), user("Bob", 3), user("Cleo", 3)],
+        };
+
+        let stats = Stats::from_report(report);
+        assert_eq!(
+            stats.weekdays,
+            vec![
+                WeekdayAverage {
+                    weekday: Weekday::Tue,
+                    lists: 3,
+                    average: 4.0,
+                },
+                WeekdayAverage {
+                    weekday: Weekday::Wed,
+                    lists: 1,
+                    average: 3.0,
+                },
+            ]
+        );
+        assert_eq!(stats.types[1].list_type.to_string(), "lunch");
+        assert_eq!(stats.types[1].average, 13.0 / 3.0);
+        let top: Vec<_> = stats.top_attendees.iter().map(|u| &u.username).collect();
+        assert_eq!(top, vec!["Bob", "Cleo", "Anna"]);
+        assert_eq!(
+            stats.daily[0],
+            DailyHeadcount {
+                date: NaiveDate::from_ymd_opt(2020, 12, 1).unwrap(),
+                headcount: 6,
+            }
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Store;
+use crate::{
+    errors::ServiceError,
+    holiday::Holiday,
+    ledger::Transaction,
+    list::{Guests, List, ListType, Rsvp},
+    recipe::{Ingredient, Recipe},
+    template::Template,
+    user::{Diet, User},
+};
+
+/// Version of the dump format, increase when making incompatible changes.
+pub const DUMP_VERSION: usize = 1;
+
+/// All data of a store, refresh tokens are not included.
+#[derive(Deserialize, Serialize)]
+pub struct Dump {
+    pub version: usize,
+    pub users: Vec<DumpUser>,
+    pub lists: Vec<DumpList>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<Template>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<Holiday>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<Transaction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<DumpRecipe>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DumpUser {
+    pub id: usize,
+    pub username: String,
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Diet::is_empty")]
+    pub diet: Diet,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DumpList {
+    pub id: usize,
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    pub date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cook: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe: Option<usize>,
+    pub users: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waitlist: Vec<usize>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub guests: BTreeMap<usize, Guests>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<usize, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub answers: BTreeMap<usize, Rsvp>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DumpRecipe {
+    pub id: usize,
+    pub name: String,
+    pub ingredients: Vec<Ingredient>,
+}
+
+impl Dump {
+    /// Returns true when the dump contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+            && self.lists.is_empty()
+            && self.templates.is_empty()
+            && self.holidays.is_empty()
+            && self.transactions.is_empty()
+            && self.recipes.is_empty()
+    }
+}
+
+/// Reads all users, lists, attendance, templates, holidays, the balance
+/// ledger and recipes from the store.
+pub async fn export(store: &dyn Store) -> Result<Dump, ServiceError> {
+    let mut users: Vec<DumpUser> = store
+        .users()
+        .await?
+        .into_iter()
+        .map(|user| DumpUser {
+            id: user.id,
+            username: user.username,
+            password: user.password,
+            diet: user.diet,
+        })
+        .collect();
+    users.sort_by_key(|user| user.id);
+
+    let mut lists = Vec::new();
+    for id in store.list_ids(0, isize::MAX as usize, true).await? {
+        let list = store.list(id).await?.ok_or(ServiceError::InternalError)?;
+        let mut attendees: Vec<usize> = store.attendee_ids(id).await?.into_iter().collect();
+        attendees.sort_unstable();
+        lists.push(DumpList {
+            id,
+            list_type: list.list_type,
+            date: list.date,
+            deadline: list.deadline,
+            capacity: list.capacity,
+            title: list.title,
+            menu: list.menu,
+            cook: list.cook,
+            recipe: list.recipe,
+            users: attendees,
+            waitlist: store.waitlist_ids(id).await?,
+            guests: store.guests(id).await?.into_iter().collect(),
+            notes: store.notes(id).await?.into_iter().collect(),
+            answers: store.answers(id).await?.into_iter().collect(),
+        });
+    }
+
+    Ok(Dump {
+        version: DUMP_VERSION,
+        users,
+        lists,
+        templates: store.templates().await?,
+        holidays: store.holidays().await?,
+        transactions: store.transactions().await?,
+        recipes: store
+            .recipes()
+            .await?
+            .into_iter()
+            .map(|recipe| DumpRecipe {
+                id: recipe.id,
+                name: recipe.name,
+                ingredients: recipe.ingredients,
+            })
+            .collect(),
+    })
+}
+
+/// Writes all users, lists, attendance, templates, holidays, the balance
+/// ledger and recipes of a dump to the store, keeping the ids of users and
+/// lists. Recipes get new ids, lists are updated to reference them.
+pub async fn import(store: &dyn Store, dump: Dump) -> Result<(), ServiceError> {
+    let mut recipe_ids = HashMap::new();
+    for recipe in dump.recipes {
+        let id = store
+            .create_recipe(&Recipe {
+                id: 0,
+                name: recipe.name,
+                ingredients: recipe.ingredients,
+            })
+            .await?;
+        recipe_ids.insert(recipe.id, id);
+    }
+
+    for user in dump.users {
+        store
+            .insert_user(&User {
+                id: user.id,
+                username: user.username,
+                password: user.password,
+                diet: user.diet,
+            })
+            .await?;
+    }
+
+    for dump_list in dump.lists {
+        let mut list = List::new(dump_list.id, dump_list.list_type, dump_list.date);
+        list.deadline = dump_list.deadline;
+        list.capacity = dump_list.capacity;
+        list.title = dump_list.title;
+        list.menu = dump_list.menu;
+        list.cook = dump_list.cook;
+        list.recipe = dump_list
+            .recipe
+            .and_then(|recipe| recipe_ids.get(&recipe).copied());
+        store.insert_list(&list).await?;
+        // Attendance is copied as is, a list may have more attendees than its
+        // capacity after the capacity was lowered.
+        let attendance = dump_list
+            .users
+            .into_iter()
+            .map(|user_id| (user_id, false))
+            .chain(
+                dump_list
+                    .waitlist
+                    .into_iter()
+                    .map(|user_id| (user_id, true)),
+            );
+        for (user_id, waitlisted) in attendance {
+            let guests = dump_list.guests.get(&user_id).cloned().unwrap_or_default();
+            let note = dump_list.notes.get(&user_id).map(String::as_str);
+            store
+                .insert_attendee(list.id, user_id, &guests, note, waitlisted)
+                .await?;
+        }
+        for (user_id, answer) in dump_list.answers {
+            store.set_answer(list.id, user_id, Some(answer)).await?;
+        }
+    }
+
+    for template in dump.templates {
+        store.create_template(&template).await?;
+    }
+    for holiday in dump.holidays {
+        store.add_holiday(&holiday).await?;
+    }
+    for transaction in dump.transactions {
+        store.add_transaction(&transaction).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MemoryStore, SqliteStore};
+
+    #[actix_rt::test]
+    async fn test_export_import_roundtrip() {
+        let store = MemoryStore::new();
+        let user_id = store.create_user("Sir User", "hash").await.unwrap();
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let recipe_id = store
+            .create_recipe(&Recipe {
+                id: 0,
+                name: "Soup".to_string(),
+                ingredients: vec![Ingredient {
+                    name: "carrot".to_string(),
+                    quantity: 1.5,
+                    unit: String::new(),
+                }],
+            })
+            .await
+            .unwrap();
+        let mut list = List::new(0, "dinner".parse().unwrap(), date);
+        list.recipe = Some(recipe_id);
+        let list_id = store.create_list(&list).await.unwrap();
+        let guests = Guests {
+            count: 2,
+            names: vec!["Guest".to_string()],
+        };
+        store
+            .add_attendee(list_id, user_id, &guests, Some("no onions"))
+            .await
+            .unwrap();
+        let other_id = store.create_user("Other User", "hash").await.unwrap();
+        store
+            .set_answer(list_id, other_id, Some(Rsvp::Maybe))
+            .await
+            .unwrap();
+
+        let dump = export(&store).await.unwrap();
+        let json = serde_json::to_string(&dump).unwrap();
+
+        let imported = MemoryStore::new();
+        import(&imported, serde_json::from_str(&json).unwrap())
+            .await
+            .unwrap();
+        let reexported = serde_json::to_string(&export(&imported).await.unwrap()).unwrap();
+        assert_eq!(json, reexported);
+
+        let next_id = imported.create_user("Third User", "hash").await.unwrap();
+        assert!(next_id > other_id);
+    }
+
+    #[actix_rt::test]
+    async fn test_import_keeps_attendance_over_capacity() {
+        let store = MemoryStore::new();
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let mut list = List::new(0, "lunch".parse().unwrap(), date);
+        list.id = store.create_list(&list).await.unwrap();
+        for username in &["First User", "Second User", "Third User"] {
+            let user_id = store.create_user(username, "hash").await.unwrap();
+            store
+                .add_attendee(list.id, user_id, &Guests::default(), None)
+                .await
+                .unwrap();
+        }
+        // Lowering the capacity leaves the attendees on the list.
+        list.capacity = Some(1);
+        store.update_list(&list).await.unwrap();
+        let late_id = store.create_user("Late User", "hash").await.unwrap();
+        store
+            .add_attendee(list.id, late_id, &Guests::default(), None)
+            .await
+            .unwrap();
+
+        let json = serde_json::to_string(&export(&store).await.unwrap()).unwrap();
+        let imported: [Box<dyn Store>; 2] = [
+            Box::new(MemoryStore::new()),
+            Box::new(SqliteStore::open_in_memory().unwrap()),
+        ];
+        for imported in &imported {
+            import(imported.as_ref(), serde_json::from_str(&json).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(imported.attendee_count(list.id).await.unwrap(), 3);
+            assert_eq!(imported.waitlist_ids(list.id).await.unwrap(), vec![late_id]);
+            let reexported = export(imported.as_ref()).await.unwrap();
+            assert_eq!(json, serde_json::to_string(&reexported).unwrap());
+        }
+    }
+}
@@ -0,0 +1,662 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::{Attendance, Store};
+use crate::{
+    errors::ServiceError,
+    holiday::Holiday,
+    ledger::Transaction,
+    list::{Guests, List, ListType, Rsvp},
+    recipe::Recipe,
+    template::Template,
+    user::{Diet, User},
+};
+
+/// Store which keeps all data in memory, everything is lost on exit.
+///
+/// Useful for tests and for running a demo server without Redis.
+#[derive(Default)]
+pub struct MemoryStore {
+    data: Mutex<Data>,
+}
+
+#[derive(Default)]
+struct Data {
+    next_user_id: usize,
+    user_ids: HashMap<String, usize>,
+    users: HashMap<usize, User>,
+    refresh_tokens: HashMap<usize, HashSet<Vec<u8>>>,
+    feed_secrets: HashMap<usize, Vec<u8>>,
+    next_list_id: usize,
+    lists: HashMap<usize, List>,
+    attendees: HashMap<usize, HashSet<usize>>,
+    waitlists: HashMap<usize, Vec<usize>>,
+    guests: HashMap<usize, HashMap<usize, Guests>>,
+    notes: HashMap<usize, HashMap<usize, String>>,
+    answers: HashMap<usize, HashMap<usize, Rsvp>>,
+    next_template_id: usize,
+    templates: BTreeMap<usize, Template>,
+    holidays: BTreeMap<NaiveDate, String>,
+    transactions: Vec<Transaction>,
+    next_recipe_id: usize,
+    recipes: BTreeMap<usize, Recipe>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> Result<MutexGuard<'_, Data>, ServiceError> {
+        self.data.lock().map_err(|_| ServiceError::InternalError)
+    }
+}
+
+impl Data {
+    /// Returns the number of seats a user takes on a list, for themselves and
+    /// their guests.
+    fn party_size(&self, list_id: usize, user_id: usize) -> usize {
+        1 + self
+            .guests
+            .get(&list_id)
+            .and_then(|guests| guests.get(&user_id))
+            .map_or(0, |guests| guests.count)
+    }
+
+    /// Returns true when a party of `seats` fits on a list next to the
+    /// attendees and their guests.
+    fn has_seats(&self, list_id: usize, seats: usize) -> bool {
+        let capacity = match self.lists.get(&list_id).and_then(|list| list.capacity) {
+            Some(capacity) => capacity,
+            None => return true,
+        };
+        let taken: usize = self.attendees.get(&list_id).map_or(0, |attendees| {
+            attendees
+                .iter()
+                .map(|user_id| self.party_size(list_id, *user_id))
+                .sum()
+        });
+        taken + seats <= capacity
+    }
+
+    /// Promotes users from the front of the waitlist of a list for as long as
+    /// their party fits.
+    fn promote_waitlist(&mut self, list_id: usize) {
+        while let Some(&next) = self.waitlists.get(&list_id).and_then(|w| w.first()) {
+            if !self.has_seats(list_id, self.party_size(list_id, next)) {
+                break;
+            }
+            self.waitlists.entry(list_id).or_default().remove(0);
+            self.attendees.entry(list_id).or_default().insert(next);
+        }
+    }
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn user_id(&self, username: &str) -> Result<Option<usize>, ServiceError> {
+        Ok(self.data()?.user_ids.get(username).copied())
+    }
+
+    async fn user(&self, id: usize) -> Result<Option<User>, ServiceError> {
+        Ok(self.data()?.users.get(&id).cloned())
+    }
+
+    async fn create_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<usize, ServiceError> {
+        let mut data = self.data()?;
+        if data.user_ids.contains_key(username) {
+            return Err(ServiceError::UserAlreadyExists {
+                username: username.to_string(),
+            });
+        }
+
+        data.next_user_id += 1;
+        let id = data.next_user_id;
+        data.user_ids.insert(username.to_string(), id);
+        data.users.insert(
+            id,
+            User {
+                id,
+                username: username.to_string(),
+                password: password_hash.to_string(),
+                diet: Diet::default(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn set_password(&self, id: usize, password_hash: &str) -> Result<bool, ServiceError> {
+        Ok(match self.data()?.users.get_mut(&id) {
+            Some(user) => {
+                user.password = password_hash.to_string();
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn set_diet(&self, id: usize, diet: &Diet) -> Result<bool, ServiceError> {
+        Ok(match self.data()?.users.get_mut(&id) {
+            Some(user) => {
+                user.diet = diet.clone();
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn delete_user(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        let user = match data.users.remove(&id) {
+            Some(user) => user,
+            None => return Ok(false),
+        };
+        data.user_ids.remove(&user.username);
+        data.refresh_tokens.remove(&id);
+        data.feed_secrets.remove(&id);
+        let mut attended = Vec::new();
+        for (list_id, attendees) in data.attendees.iter_mut() {
+            if attendees.remove(&id) {
+                attended.push(*list_id);
+            }
+        }
+        for waitlist in data.waitlists.values_mut() {
+            waitlist.retain(|user_id| *user_id != id);
+        }
+        for guests in data.guests.values_mut() {
+            guests.remove(&id);
+        }
+        for notes in data.notes.values_mut() {
+            notes.remove(&id);
+        }
+        for answers in data.answers.values_mut() {
+            answers.remove(&id);
+        }
+        for list_id in attended {
+            data.promote_waitlist(list_id);
+        }
+        Ok(true)
+    }
+
+    async fn users(&self) -> Result<Vec<User>, ServiceError> {
+        Ok(self.data()?.users.values().cloned().collect())
+    }
+
+    async fn insert_user(&self, user: &User) -> Result<(), ServiceError> {
+        let mut data = self.data()?;
+        data.next_user_id = data.next_user_id.max(user.id);
+        data.user_ids.insert(user.username.clone(), user.id);
+        data.users.insert(user.id, user.clone());
+        Ok(())
+    }
+
+    async fn add_refresh_token(&self, user_id: usize, digest: &[u8]) -> Result<(), ServiceError> {
+        self.data()?
+            .refresh_tokens
+            .entry(user_id)
+            .or_default()
+            .insert(digest.to_vec());
+        Ok(())
+    }
+
+    async fn remove_refresh_token(
+        &self,
+        user_id: usize,
+        digest: &[u8],
+    ) -> Result<bool, ServiceError> {
+        Ok(self
+            .data()?
+            .refresh_tokens
+            .get_mut(&user_id)
+            .is_some_and(|tokens| tokens.remove(digest)))
+    }
+
+    async fn clear_refresh_tokens(&self, user_id: usize) -> Result<(), ServiceError> {
+        self.data()?.refresh_tokens.remove(&user_id);
+        Ok(())
+    }
+
+    async fn feed_secret(&self, user_id: usize) -> Result<Option<Vec<u8>>, ServiceError> {
+        Ok(self.data()?.feed_secrets.get(&user_id).cloned())
+    }
+
+    async fn set_feed_secret(&self, user_id: usize, secret: &[u8]) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        if !data.users.contains_key(&user_id) {
+            return Ok(false);
+        }
+        data.feed_secrets.insert(user_id, secret.to_vec());
+        Ok(true)
+    }
+
+    async fn list(&self, id: usize) -> Result<Option<List>, ServiceError> {
+        Ok(self.data()?.lists.get(&id).cloned())
+    }
+
+    async fn list_ids(
+        &self,
+        start: usize,
+        stop: usize,
+        rev: bool,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let data = self.data()?;
+        let mut lists: Vec<&List> = data.lists.values().collect();
+        lists.sort_by_key(|list| (list.date, list.id));
+        if !rev {
+            lists.reverse();
+        }
+        Ok(lists
+            .into_iter()
+            .skip(start)
+            .take((stop + 1).saturating_sub(start))
+            .map(|list| list.id)
+            .collect())
+    }
+
+    async fn list_ids_on(&self, date: NaiveDate) -> Result<Vec<usize>, ServiceError> {
+        Ok(self
+            .data()?
+            .lists
+            .values()
+            .filter(|list| list.date == date)
+            .map(|list| list.id)
+            .collect())
+    }
+
+    async fn list_ids_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        list_type: Option<&ListType>,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let data = self.data()?;
+        let mut lists: Vec<&List> = data
+            .lists
+            .values()
+            .filter(|list| start.is_none_or(|start| start <= list.date))
+            .filter(|list| end.is_none_or(|end| list.date <= end))
+            .filter(|list| list_type.is_none_or(|list_type| list_type == &list.list_type))
+            .collect();
+        lists.sort_by_key(|list| (list.date, list.id));
+        Ok(lists.into_iter().map(|list| list.id).collect())
+    }
+
+    async fn create_list(&self, list: &List) -> Result<usize, ServiceError> {
+        let mut data = self.data()?;
+        let existing = data
+            .lists
+            .values()
+            .find(|existing| existing.date == list.date && existing.list_type == list.list_type);
+        if let Some(existing) = existing {
+            return Err(ServiceError::ListAlreadyExists { id: existing.id });
+        }
+
+        data.next_list_id += 1;
+        let id = data.next_list_id;
+        let mut list = list.clone();
+        list.id = id;
+        data.lists.insert(id, list);
+        Ok(id)
+    }
+
+    async fn insert_list(&self, list: &List) -> Result<(), ServiceError> {
+        let mut data = self.data()?;
+        data.next_list_id = data.next_list_id.max(list.id);
+        data.lists.insert(list.id, list.clone());
+        Ok(())
+    }
+
+    async fn update_list(&self, list: &List) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        let existing = data.lists.values().find(|existing| {
+            existing.id != list.id
+                && existing.date == list.date
+                && existing.list_type == list.list_type
+        });
+        if let Some(existing) = existing {
+            return Err(ServiceError::ListAlreadyExists { id: existing.id });
+        }
+
+        Ok(match data.lists.get_mut(&list.id) {
+            Some(existing) => {
+                *existing = list.clone();
+                data.promote_waitlist(list.id);
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn delete_list(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        data.attendees.remove(&id);
+        data.waitlists.remove(&id);
+        data.guests.remove(&id);
+        data.notes.remove(&id);
+        data.answers.remove(&id);
+        Ok(data.lists.remove(&id).is_some())
+    }
+
+    async fn attendees(&self, list_id: usize) -> Result<HashSet<String>, ServiceError> {
+        let data = self.data()?;
+        Ok(data
+            .attendees
+            .get(&list_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| data.users.get(id))
+            .map(|user| user.username.clone())
+            .collect())
+    }
+
+    async fn attendee_ids(&self, list_id: usize) -> Result<HashSet<usize>, ServiceError> {
+        Ok(self
+            .data()?
+            .attendees
+            .get(&list_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn attendee_count(&self, list_id: usize) -> Result<usize, ServiceError> {
+        Ok(self.data()?.attendees.get(&list_id).map_or(0, HashSet::len))
+    }
+
+    async fn waitlist(&self, list_id: usize) -> Result<Vec<String>, ServiceError> {
+        let data = self.data()?;
+        Ok(data
+            .waitlists
+            .get(&list_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| data.users.get(id))
+            .map(|user| user.username.clone())
+            .collect())
+    }
+
+    async fn waitlist_ids(&self, list_id: usize) -> Result<Vec<usize>, ServiceError> {
+        Ok(self
+            .data()?
+            .waitlists
+            .get(&list_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn guests(&self, list_id: usize) -> Result<HashMap<usize, Guests>, ServiceError> {
+        Ok(self
+            .data()?
+            .guests
+            .get(&list_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn notes(&self, list_id: usize) -> Result<HashMap<usize, String>, ServiceError> {
+        Ok(self
+            .data()?
+            .notes
+            .get(&list_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn answers(&self, list_id: usize) -> Result<HashMap<usize, Rsvp>, ServiceError> {
+        Ok(self
+            .data()?
+            .answers
+            .get(&list_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn set_answer(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        answer: Option<Rsvp>,
+    ) -> Result<(), ServiceError> {
+        let mut data = self.data()?;
+        let answers = data.answers.entry(list_id).or_default();
+        match answer {
+            Some(answer) => answers.insert(user_id, answer),
+            None => answers.remove(&user_id),
+        };
+        Ok(())
+    }
+
+    async fn add_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+    ) -> Result<Attendance, ServiceError> {
+        let mut data = self.data()?;
+        let attending = data
+            .attendees
+            .get(&list_id)
+            .is_some_and(|attendees| attendees.contains(&user_id));
+        let party = 1 + guests.count;
+        let previous = data.party_size(list_id, user_id);
+        if attending && party > previous && !data.has_seats(list_id, party - previous) {
+            return Err(ServiceError::NotEnoughSeats);
+        }
+
+        let list_guests = data.guests.entry(list_id).or_default();
+        if guests.count > 0 {
+            list_guests.insert(user_id, guests.clone());
+        } else {
+            list_guests.remove(&user_id);
+        }
+        let list_notes = data.notes.entry(list_id).or_default();
+        match note {
+            Some(note) => list_notes.insert(user_id, note.to_string()),
+            None => list_notes.remove(&user_id),
+        };
+
+        let waitlisted = data
+            .waitlists
+            .get(&list_id)
+            .is_some_and(|waitlist| waitlist.contains(&user_id));
+        if attending || waitlisted {
+            if party < previous {
+                data.promote_waitlist(list_id);
+            }
+            let promoted = waitlisted
+                && data
+                    .attendees
+                    .get(&list_id)
+                    .is_some_and(|attendees| attendees.contains(&user_id));
+            return Ok(if promoted {
+                Attendance::Joined
+            } else {
+                Attendance::Unchanged
+            });
+        }
+        Ok(
+            if data.has_seats(list_id, data.party_size(list_id, user_id)) {
+                data.attendees.entry(list_id).or_default().insert(user_id);
+                Attendance::Joined
+            } else {
+                data.waitlists.entry(list_id).or_default().push(user_id);
+                Attendance::Waitlisted
+            },
+        )
+    }
+
+    async fn remove_attendee(&self, list_id: usize, user_id: usize) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        if let Some(guests) = data.guests.get_mut(&list_id) {
+            guests.remove(&user_id);
+        }
+        if let Some(notes) = data.notes.get_mut(&list_id) {
+            notes.remove(&user_id);
+        }
+        if data
+            .attendees
+            .get_mut(&list_id)
+            .is_some_and(|attendees| attendees.remove(&user_id))
+        {
+            data.promote_waitlist(list_id);
+            Ok(true)
+        } else if let Some(waitlist) = data.waitlists.get_mut(&list_id) {
+            let len = waitlist.len();
+            waitlist.retain(|id| *id != user_id);
+            Ok(waitlist.len() < len)
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn insert_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+        waitlisted: bool,
+    ) -> Result<(), ServiceError> {
+        let mut data = self.data()?;
+        if guests.count > 0 {
+            data.guests
+                .entry(list_id)
+                .or_default()
+                .insert(user_id, guests.clone());
+        }
+        if let Some(note) = note {
+            data.notes
+                .entry(list_id)
+                .or_default()
+                .insert(user_id, note.to_string());
+        }
+        if waitlisted {
+            data.waitlists.entry(list_id).or_default().push(user_id);
+        } else {
+            data.attendees.entry(list_id).or_default().insert(user_id);
+        }
+        Ok(())
+    }
+
+    async fn templates(&self) -> Result<Vec<Template>, ServiceError> {
+        Ok(self.data()?.templates.values().cloned().collect())
+    }
+
+    async fn create_template(&self, template: &Template) -> Result<usize, ServiceError> {
+        let mut data = self.data()?;
+        data.next_template_id += 1;
+        let id = data.next_template_id;
+        data.templates.insert(
+            id,
+            Template {
+                id,
+                ..template.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    async fn delete_template(&self, id: usize) -> Result<bool, ServiceError> {
+        Ok(self.data()?.templates.remove(&id).is_some())
+    }
+
+    async fn set_generated_until(&self, id: usize, date: NaiveDate) -> Result<(), ServiceError> {
+        if let Some(template) = self.data()?.templates.get_mut(&id) {
+            template.generated_until = Some(date);
+        }
+        Ok(())
+    }
+
+    async fn holidays(&self) -> Result<Vec<Holiday>, ServiceError> {
+        Ok(self
+            .data()?
+            .holidays
+            .iter()
+            .map(|(date, name)| Holiday {
+                date: *date,
+                name: name.clone(),
+            })
+            .collect())
+    }
+
+    async fn holiday(&self, date: NaiveDate) -> Result<Option<Holiday>, ServiceError> {
+        Ok(self.data()?.holidays.get(&date).map(|name| Holiday {
+            date,
+            name: name.clone(),
+        }))
+    }
+
+    async fn add_holiday(&self, holiday: &Holiday) -> Result<(), ServiceError> {
+        self.data()?
+            .holidays
+            .insert(holiday.date, holiday.name.clone());
+        Ok(())
+    }
+
+    async fn delete_holiday(&self, date: NaiveDate) -> Result<bool, ServiceError> {
+        Ok(self.data()?.holidays.remove(&date).is_some())
+    }
+
+    async fn transactions(&self) -> Result<Vec<Transaction>, ServiceError> {
+        Ok(self.data()?.transactions.clone())
+    }
+
+    async fn add_transaction(&self, transaction: &Transaction) -> Result<usize, ServiceError> {
+        let mut data = self.data()?;
+        let id = data.transactions.len() + 1;
+        data.transactions.push(Transaction {
+            id,
+            ..transaction.clone()
+        });
+        Ok(id)
+    }
+
+    async fn recipes(&self) -> Result<Vec<Recipe>, ServiceError> {
+        Ok(self.data()?.recipes.values().cloned().collect())
+    }
+
+    async fn recipe(&self, id: usize) -> Result<Option<Recipe>, ServiceError> {
+        Ok(self.data()?.recipes.get(&id).cloned())
+    }
+
+    async fn create_recipe(&self, recipe: &Recipe) -> Result<usize, ServiceError> {
+        let mut data = self.data()?;
+        data.next_recipe_id += 1;
+        let id = data.next_recipe_id;
+        data.recipes.insert(
+            id,
+            Recipe {
+                id,
+                ..recipe.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    async fn delete_recipe(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut data = self.data()?;
+        for list in data.lists.values_mut() {
+            if list.recipe == Some(id) {
+                list.recipe = None;
+            }
+        }
+        Ok(data.recipes.remove(&id).is_some())
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, ServiceError> {
+        Ok(0)
+    }
+
+    async fn migrate(&self) -> Result<usize, ServiceError> {
+        Ok(0)
+    }
+}
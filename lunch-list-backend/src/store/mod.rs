@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::{
+    errors::ServiceError,
+    holiday::Holiday,
+    ledger::Transaction,
+    list::{Guests, List, ListType, Rsvp},
+    recipe::Recipe,
+    template::Template,
+    user::{Diet, User},
+};
+
+pub mod dump;
+mod memory_store;
+mod redis_migrations;
+mod redis_store;
+mod sqlite_store;
+
+pub use memory_store::MemoryStore;
+pub use redis_store::RedisStore;
+pub use sqlite_store::SqliteStore;
+
+/// Result of adding a user to a list.
+#[derive(Debug, PartialEq)]
+pub enum Attendance {
+    /// The user was added to the attendees, or promoted from the waitlist
+    /// by bringing fewer guests.
+    Joined,
+    /// The user and their guests do not fit, the user was added to the
+    /// waitlist.
+    Waitlisted,
+    /// The user already attends or is still on the waitlist.
+    Unchanged,
+}
+
+/// Persistent storage used by the API routes.
+///
+/// Routes receive the store as `web::Data<dyn Store>`, so any backend
+/// implementing this trait can be plugged into the server.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Returns the id of the user with the given username.
+    async fn user_id(&self, username: &str) -> Result<Option<usize>, ServiceError>;
+
+    /// Returns the user with the given id.
+    async fn user(&self, id: usize) -> Result<Option<User>, ServiceError>;
+
+    /// Creates a new user and returns its id.
+    ///
+    /// Fails with `ServiceError::UserAlreadyExists` when the username is taken.
+    async fn create_user(&self, username: &str, password_hash: &str)
+        -> Result<usize, ServiceError>;
+
+    /// Replaces the password hash of a user, returns true when the user exists.
+    async fn set_password(&self, id: usize, password_hash: &str) -> Result<bool, ServiceError>;
+
+    /// Replaces the dietary preferences of a user, returns true when the user
+    /// exists.
+    async fn set_diet(&self, id: usize, diet: &Diet) -> Result<bool, ServiceError>;
+
+    /// Deletes a user, its refresh tokens and its attendance, returns true
+    /// when the user existed.
+    async fn delete_user(&self, id: usize) -> Result<bool, ServiceError>;
+
+    /// Returns all users.
+    async fn users(&self) -> Result<Vec<User>, ServiceError>;
+
+    /// Inserts a user with the id it already has, used when importing data.
+    async fn insert_user(&self, user: &User) -> Result<(), ServiceError>;
+
+    /// Stores the digest of a refresh token issued to a user.
+    async fn add_refresh_token(&self, user_id: usize, digest: &[u8]) -> Result<(), ServiceError>;
+
+    /// Removes the digest of a refresh token, returns true when it was present.
+    async fn remove_refresh_token(
+        &self,
+        user_id: usize,
+        digest: &[u8],
+    ) -> Result<bool, ServiceError>;
+
+    /// Removes all refresh token digests of a user.
+    async fn clear_refresh_tokens(&self, user_id: usize) -> Result<(), ServiceError>;
+
+    /// Returns the secret that the calendar feed token of a user is derived
+    /// from, `None` until one is set.
+    async fn feed_secret(&self, user_id: usize) -> Result<Option<Vec<u8>>, ServiceError>;
+
+    /// Replaces the calendar feed secret of a user, returns true when the user
+    /// exists. The secret is deleted with the user.
+    async fn set_feed_secret(&self, user_id: usize, secret: &[u8]) -> Result<bool, ServiceError>;
+
+    /// Returns the list with the given id.
+    async fn list(&self, id: usize) -> Result<Option<List>, ServiceError>;
+
+    /// Returns list ids ordered by date, newest first, or oldest first when
+    /// `rev` is set. `start` and `stop` are inclusive offsets.
+    async fn list_ids(
+        &self,
+        start: usize,
+        stop: usize,
+        rev: bool,
+    ) -> Result<Vec<usize>, ServiceError>;
+
+    /// Returns the ids of all lists on the given date.
+    async fn list_ids_on(&self, date: NaiveDate) -> Result<Vec<usize>, ServiceError>;
+
+    /// Returns the ids of all lists from `start` up to and including `end`,
+    /// ordered by date, oldest first. A missing bound is unlimited. Only lists
+    /// of `list_type` are returned when it is given.
+    async fn list_ids_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        list_type: Option<&ListType>,
+    ) -> Result<Vec<usize>, ServiceError>;
+
+    /// Creates a new list and returns its id.
+    ///
+    /// Fails with `ServiceError::ListAlreadyExists` when a list of the same
+    /// type already exists on that date.
+    async fn create_list(&self, list: &List) -> Result<usize, ServiceError>;
+
+    /// Inserts a list with the id it already has, used when importing data.
+    async fn insert_list(&self, list: &List) -> Result<(), ServiceError>;
+
+    /// Replaces an existing list, returns true when the list exists. Users on
+    /// the waitlist are promoted when the new capacity leaves room for them.
+    ///
+    /// Fails with `ServiceError::ListAlreadyExists` when another list of the
+    /// same type exists on the new date.
+    async fn update_list(&self, list: &List) -> Result<bool, ServiceError>;
+
+    /// Deletes a list and its attendance, returns true when the list existed.
+    async fn delete_list(&self, id: usize) -> Result<bool, ServiceError>;
+
+    /// Returns the usernames of the attendees of a list.
+    async fn attendees(&self, list_id: usize) -> Result<HashSet<String>, ServiceError>;
+
+    /// Returns the user ids of the attendees of a list.
+    async fn attendee_ids(&self, list_id: usize) -> Result<HashSet<usize>, ServiceError>;
+
+    /// Returns the number of attendees of a list.
+    async fn attendee_count(&self, list_id: usize) -> Result<usize, ServiceError>;
+
+    /// Returns the usernames on the waitlist of a list, first in line first.
+    async fn waitlist(&self, list_id: usize) -> Result<Vec<String>, ServiceError>;
+
+    /// Returns the user ids on the waitlist of a list, first in line first.
+    async fn waitlist_ids(&self, list_id: usize) -> Result<Vec<usize>, ServiceError>;
+
+    /// Returns the guests brought by attendees and waitlisted users of a
+    /// list, by user id.
+    async fn guests(&self, list_id: usize) -> Result<HashMap<usize, Guests>, ServiceError>;
+
+    /// Returns the notes left by attendees and waitlisted users of a list, by
+    /// user id.
+    async fn notes(&self, list_id: usize) -> Result<HashMap<usize, String>, ServiceError>;
+
+    /// Returns the answers of users who declined or may attend a list, by
+    /// user id. Attendees are not included.
+    async fn answers(&self, list_id: usize) -> Result<HashMap<usize, Rsvp>, ServiceError>;
+
+    /// Records that a user declined or may attend a list, or clears the
+    /// answer of the user with `None`.
+    async fn set_answer(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        answer: Option<Rsvp>,
+    ) -> Result<(), ServiceError>;
+
+    /// Adds a user with its guests and note to a list, or to the end of its
+    /// waitlist when the user and their guests do not fit the capacity. The
+    /// guests and note of a user who is already present are replaced; fails
+    /// with `ServiceError::NotEnoughSeats` when an attendee brings more guests
+    /// than there are seats left, and promotes the waitlist when the party
+    /// shrinks.
+    async fn add_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+    ) -> Result<Attendance, ServiceError>;
+
+    /// Removes a user with its guests and note from a list or its waitlist,
+    /// returns true when it was present. Users at the front of the waitlist
+    /// are promoted to attendee for as long as they and their guests fit.
+    async fn remove_attendee(&self, list_id: usize, user_id: usize) -> Result<bool, ServiceError>;
+
+    /// Adds a user with its guests and note to the attendees of a list, or to
+    /// the end of its waitlist when `waitlisted` is set, without checking the
+    /// capacity. Used when importing data.
+    async fn insert_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+        waitlisted: bool,
+    ) -> Result<(), ServiceError>;
+
+    /// Returns all recurring list templates, ordered by id.
+    async fn templates(&self) -> Result<Vec<Template>, ServiceError>;
+
+    /// Creates a new recurring list template and returns its id.
+    async fn create_template(&self, template: &Template) -> Result<usize, ServiceError>;
+
+    /// Deletes a recurring list template, returns true when it existed. Lists
+    /// that were already created from it are kept.
+    async fn delete_template(&self, id: usize) -> Result<bool, ServiceError>;
+
+    /// Records that the lists of a template were created up to and including
+    /// `date`.
+    async fn set_generated_until(&self, id: usize, date: NaiveDate) -> Result<(), ServiceError>;
+
+    /// Returns all holidays, ordered by date.
+    async fn holidays(&self) -> Result<Vec<Holiday>, ServiceError>;
+
+    /// Returns the holiday on the given date.
+    async fn holiday(&self, date: NaiveDate) -> Result<Option<Holiday>, ServiceError>;
+
+    /// Adds a holiday, replacing the name of an existing holiday on its date.
+    async fn add_holiday(&self, holiday: &Holiday) -> Result<(), ServiceError>;
+
+    /// Deletes the holiday on the given date, returns true when it existed.
+    async fn delete_holiday(&self, date: NaiveDate) -> Result<bool, ServiceError>;
+
+    /// Returns all transactions of the balance ledger, ordered by id.
+    async fn transactions(&self) -> Result<Vec<Transaction>, ServiceError>;
+
+    /// Adds a transaction to the balance ledger and returns its id.
+    async fn add_transaction(&self, transaction: &Transaction) -> Result<usize, ServiceError>;
+
+    /// Returns all recipes, ordered by id.
+    async fn recipes(&self) -> Result<Vec<Recipe>, ServiceError>;
+
+    /// Returns the recipe with the given id.
+    async fn recipe(&self, id: usize) -> Result<Option<Recipe>, ServiceError>;
+
+    /// Creates a new recipe and returns its id.
+    async fn create_recipe(&self, recipe: &Recipe) -> Result<usize, ServiceError>;
+
+    /// Deletes a recipe, returns true when it existed. Lists that reference
+    /// it are kept without a recipe.
+    async fn delete_recipe(&self, id: usize) -> Result<bool, ServiceError>;
+
+    /// Returns the number of data migrations that have to be applied with
+    /// `Store::migrate` before the server can use this store.
+    async fn pending_migrations(&self) -> Result<usize, ServiceError>;
+
+    /// Upgrades stored data to the latest schema version in place, returns
+    /// the number of applied migrations.
+    async fn migrate(&self) -> Result<usize, ServiceError>;
+}
@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+
+use mobc_redis::{
+    redis,
+    redis::{aio::Connection, AsyncCommands},
+};
+
+use super::redis_store::index_field;
+use crate::{errors::ServiceError, list::List};
+
+/// Latest version of the Redis data layout.
+pub const SCHEMA_VERSION: usize = 2;
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Returns the schema version of the stored data.
+///
+/// Data written before the version key was introduced is version 0. A
+/// database without any data is stamped with the latest version.
+pub async fn current_version(conn: &mut Connection) -> Result<usize, ServiceError> {
+    let version: Option<usize> = conn.get(SCHEMA_VERSION_KEY).await?;
+    if let Some(version) = version {
+        return Ok(version);
+    }
+
+    let has_data: bool = conn.exists(&["next_user_id", "next_list_id"]).await?;
+    if has_data {
+        Ok(0)
+    } else {
+        conn.set::<_, _, ()>(SCHEMA_VERSION_KEY, SCHEMA_VERSION)
+            .await?;
+        Ok(SCHEMA_VERSION)
+    }
+}
+
+/// Applies all pending migrations, returns the number of applied migrations.
+pub async fn migrate(conn: &mut Connection) -> Result<usize, ServiceError> {
+    let version = current_version(conn).await?;
+    for from in version..SCHEMA_VERSION {
+        log::info!("Migrating Redis data to schema version {}", from + 1);
+        match from {
+            0 => attendance_to_user_ids(conn).await?,
+            1 => index_lists_by_date_and_type(conn).await?,
+            _ => unreachable!("missing migration from schema version {}", from),
+        }
+        conn.set::<_, _, ()>(SCHEMA_VERSION_KEY, from + 1).await?;
+    }
+    Ok(SCHEMA_VERSION.saturating_sub(version))
+}
+
+/// Attendance sets `users:{id}` used to contain usernames, they now contain
+/// user ids.
+///
+/// Every set is converted atomically, but the version is only bumped at the
+/// end. Sets that only contain ids of existing users were converted by an
+/// interrupted run and are kept as they are.
+async fn attendance_to_user_ids(conn: &mut Connection) -> Result<(), ServiceError> {
+    let user_ids: HashMap<String, usize> = conn.hgetall("users").await?;
+    let existing: HashSet<usize> = user_ids.values().copied().collect();
+    let list_ids: Vec<usize> = conn.zrange("dates", 0, -1).await?;
+    for list_id in list_ids {
+        let key = format!("users:{}", list_id);
+        let members: Vec<String> = conn.smembers(&key).await?;
+        let converted = members
+            .iter()
+            .all(|member| member.parse().is_ok_and(|id: usize| existing.contains(&id)));
+        if converted {
+            continue;
+        }
+        let ids: Vec<usize> = members
+            .iter()
+            .filter_map(|member| {
+                let id = user_ids.get(member).copied();
+                if id.is_none() {
+                    log::warn!("Dropping unknown user '{}' from list {}", member, list_id);
+                }
+                id
+            })
+            .collect();
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(&key).ignore();
+        if !ids.is_empty() {
+            pipe.sadd(&key, ids).ignore();
+        }
+        pipe.query_async::<_, ()>(conn).await?;
+    }
+    Ok(())
+}
+
+/// Lists are unique per date and type, the `list_index` hash maps both to the
+/// list id. Of duplicate lists, only the oldest one is indexed.
+async fn index_lists_by_date_and_type(conn: &mut Connection) -> Result<(), ServiceError> {
+    let mut list_ids: Vec<usize> = conn.zrange("dates", 0, -1).await?;
+    list_ids.sort_unstable();
+    for list_id in list_ids {
+        let hash = conn.hgetall(format!("list:{}", list_id)).await?;
+        let list = match List::from_hash(list_id, hash) {
+            Some(list) => list,
+            None => {
+                log::warn!("Skipping invalid list {}", list_id);
+                continue;
+            }
+        };
+        let indexed: bool = conn
+            .hset_nx("list_index", index_field(&list), list_id)
+            .await?;
+        if !indexed {
+            log::warn!(
+                "List {} duplicates another {} list on {}",
+                list_id,
+                list.list_type,
+                list.date
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::DerefMut;
+
+    use super::*;
+    use crate::store::redis_store::tests::test_pool;
+
+    #[actix_rt::test]
+    #[ignore = "requires a Redis server"]
+    async fn test_migrate() {
+        let pool = test_pool(14).await;
+        let mut conn = pool.get().await.unwrap();
+        let conn = conn.deref_mut();
+
+        // Version 0 data, where list 2 was already converted by an
+        // interrupted migration.
+        redis::pipe()
+            .hset("users", "Sir User", 1)
+            .hset("users", "Other User", 2)
+            .set("next_user_id", 2)
+            .set("next_list_id", 2)
+            .hset_multiple("list:1", &[("type", "lunch"), ("date", "2020-12-24")])
+            .hset_multiple("list:2", &[("type", "lunch"), ("date", "2020-12-25")])
+            .zadd("dates", 1, 737783)
+            .zadd("dates", 2, 737784)
+            .sadd("users:1", &["Sir User", "Other User", "Unknown User"])
+            .sadd("users:2", &["2"])
+            .query_async::<_, ()>(conn)
+            .await
+            .unwrap();
+
+        assert_eq!(current_version(conn).await.unwrap(), 0);
+        assert_eq!(migrate(conn).await.unwrap(), SCHEMA_VERSION);
+        let attendees: HashSet<usize> = conn.smembers("users:1").await.unwrap();
+        assert_eq!(attendees, vec![1, 2].into_iter().collect());
+        let attendees: HashSet<usize> = conn.smembers("users:2").await.unwrap();
+        assert_eq!(attendees, vec![2].into_iter().collect());
+        let indexed: Option<usize> = conn.hget("list_index", "2020-12-25:lunch").await.unwrap();
+        assert_eq!(indexed, Some(2));
+
+        assert_eq!(migrate(conn).await.unwrap(), 0);
+        let attendees: HashSet<usize> = conn.smembers("users:1").await.unwrap();
+        assert_eq!(attendees.len(), 2);
+    }
+}
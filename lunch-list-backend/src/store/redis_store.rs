@@ -0,0 +1,1075 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::DerefMut,
+};
+
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use mobc_redis::{
+    redis,
+    redis::{aio::Connection, AsyncCommands},
+    RedisConnectionManager,
+};
+
+use super::{redis_migrations, Attendance, Store};
+use crate::{
+    errors::ServiceError,
+    holiday::Holiday,
+    ledger::Transaction,
+    list::{Guests, List, ListType, Rsvp},
+    recipe::Recipe,
+    template::Template,
+    user::{Diet, User},
+};
+
+pub type Pool = mobc::Pool<RedisConnectionManager>;
+
+/// Store backed by a Redis server.
+pub struct RedisStore {
+    pool: Pool,
+}
+
+impl RedisStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Writes the list hash and date index of a list.
+fn write_list(pipe: &mut redis::Pipeline, id: usize, list: &List) {
+    let key = format!("list:{}", id);
+    pipe.hset_multiple(
+        &key,
+        &[
+            ("type", list.list_type.to_string()),
+            ("date", list.date.to_string()),
+        ],
+    )
+    .ignore()
+    .zadd("dates", id, list.date.num_days_from_ce())
+    .ignore();
+    match list.deadline {
+        Some(deadline) => pipe.hset(&key, "deadline", deadline.to_rfc3339()).ignore(),
+        None => pipe.hdel(&key, "deadline").ignore(),
+    };
+    match list.capacity {
+        Some(capacity) => pipe.hset(&key, "capacity", capacity).ignore(),
+        None => pipe.hdel(&key, "capacity").ignore(),
+    };
+    match list.recipe {
+        Some(recipe) => pipe.hset(&key, "recipe", recipe).ignore(),
+        None => pipe.hdel(&key, "recipe").ignore(),
+    };
+    for (field, value) in &[
+        ("title", &list.title),
+        ("menu", &list.menu),
+        ("cook", &list.cook),
+    ] {
+        match value {
+            Some(value) => pipe.hset(&key, *field, value).ignore(),
+            None => pipe.hdel(&key, *field).ignore(),
+        };
+    }
+}
+
+/// Prepends the Lua functions shared by the attendee scripts, which expect the
+/// attendees set in `KEYS[1]`, the waitlist in `KEYS[2]`, the list hash in
+/// `KEYS[3]` and the guests hash in `KEYS[4]`. A user takes one seat for
+/// themselves and one per guest.
+macro_rules! seats_script {
+    ($script:literal) => {
+        concat!(
+            r#"
+local function party_size(id)
+    local guests = redis.call('HGET', KEYS[4], id)
+    if guests then
+        return 1 + cjson.decode(guests).count
+    end
+    return 1
+end
+local function has_seats(seats)
+    local capacity = tonumber(redis.call('HGET', KEYS[3], 'capacity'))
+    if capacity == nil then
+        return true
+    end
+    local taken = 0
+    for _, id in ipairs(redis.call('SMEMBERS', KEYS[1])) do
+        taken = taken + party_size(id)
+    end
+    return taken + seats <= capacity
+end
+local function promote_waitlist()
+    while true do
+        local next = redis.call('LINDEX', KEYS[2], 0)
+        if not next or not has_seats(party_size(next)) then
+            return
+        end
+        redis.call('LPOP', KEYS[2])
+        redis.call('SADD', KEYS[1], next)
+    end
+end
+"#,
+            $script
+        )
+    };
+}
+
+/// Adds a user to the attendees set `KEYS[1]`, or to the waitlist `KEYS[2]`
+/// when their party does not fit the capacity stored in the list hash
+/// `KEYS[3]`. The guests `ARGV[2]` and note `ARGV[3]` are stored in the
+/// hashes `KEYS[4]` and `KEYS[5]`, or removed when empty. A smaller party
+/// of a user who is already present frees seats for the waitlist. Returns 1
+/// when joined, 2 when waitlisted, 0 when already present and 3 when an
+/// attendee brings more guests than there are seats left.
+const ADD_ATTENDEE_SCRIPT: &str = seats_script!(
+    r#"
+local attending = redis.call('SISMEMBER', KEYS[1], ARGV[1]) == 1
+local party = 1
+if ARGV[2] ~= '' then
+    party = 1 + cjson.decode(ARGV[2]).count
+end
+local previous = party_size(ARGV[1])
+if attending and party > previous and not has_seats(party - previous) then
+    return 3
+end
+for i = 2, 3 do
+    if ARGV[i] == '' then
+        redis.call('HDEL', KEYS[i + 2], ARGV[1])
+    else
+        redis.call('HSET', KEYS[i + 2], ARGV[1], ARGV[i])
+    end
+end
+local waitlisted = false
+for _, id in ipairs(redis.call('LRANGE', KEYS[2], 0, -1)) do
+    if id == ARGV[1] then
+        waitlisted = true
+    end
+end
+if attending or waitlisted then
+    if party < previous then
+        promote_waitlist()
+    end
+    if waitlisted and redis.call('SISMEMBER', KEYS[1], ARGV[1]) == 1 then
+        return 1
+    end
+    return 0
+end
+if has_seats(party_size(ARGV[1])) then
+    redis.call('SADD', KEYS[1], ARGV[1])
+    return 1
+end
+redis.call('RPUSH', KEYS[2], ARGV[1])
+return 2
+"#
+);
+
+/// Removes a user from the attendees set `KEYS[1]` or the waitlist `KEYS[2]`
+/// with its guests and note from `KEYS[4]` and `KEYS[5]`, and promotes users
+/// from the front of the waitlist for as long as their party fits. Returns 1
+/// when the user was present.
+const REMOVE_ATTENDEE_SCRIPT: &str = seats_script!(
+    r#"
+redis.call('HDEL', KEYS[4], ARGV[1])
+redis.call('HDEL', KEYS[5], ARGV[1])
+if redis.call('SREM', KEYS[1], ARGV[1]) == 0 then
+    return math.min(redis.call('LREM', KEYS[2], 0, ARGV[1]), 1)
+end
+promote_waitlist()
+return 1
+"#
+);
+
+/// Promotes users from the front of the waitlist `KEYS[2]` to the attendees
+/// `KEYS[1]` for as long as their party fits, e.g. after the capacity was
+/// raised.
+const PROMOTE_WAITLIST_SCRIPT: &str = seats_script!(
+    r#"
+promote_waitlist()
+"#
+);
+
+/// Runs `PROMOTE_WAITLIST_SCRIPT` for a list.
+async fn promote_waitlist(conn: &mut Connection, list_id: usize) -> Result<(), ServiceError> {
+    redis::Script::new(PROMOTE_WAITLIST_SCRIPT)
+        .key(format!("users:{}", list_id))
+        .key(format!("waitlist:{}", list_id))
+        .key(format!("list:{}", list_id))
+        .key(format!("guests:{}", list_id))
+        .invoke_async(conn)
+        .await
+        .map_err(ServiceError::from)
+}
+
+/// Reserves the field `ARGV[1]` of the `list_index` hash `KEYS[1]` for a new
+/// list id taken from the counter `KEYS[2]`. Returns whether the field was
+/// free together with the new id, or with the id already stored in it, so a
+/// conflict does not use up an id.
+const RESERVE_LIST_SCRIPT: &str = r#"
+local existing = redis.call('HGET', KEYS[1], ARGV[1])
+if existing then
+    return {0, tonumber(existing)}
+end
+local id = redis.call('INCR', KEYS[2])
+redis.call('HSET', KEYS[1], ARGV[1], id)
+return {1, id}
+"#;
+
+/// Field of a list in the `list_index` hash, which maps date and type to the
+/// id of the list.
+pub(super) fn index_field(list: &List) -> String {
+    date_type_field(list.date, &list.list_type)
+}
+
+fn date_type_field(date: NaiveDate, list_type: &ListType) -> String {
+    format!("{}:{}", date, list_type)
+}
+
+/// The `username`, `password` and `diet` fields of a user hash.
+type UserFields = (Option<String>, Option<String>, Option<String>);
+
+fn user_from_fields(id: usize, (username, password, diet): UserFields) -> Option<User> {
+    Some(User {
+        id,
+        username: username?,
+        password: password?,
+        diet: diet
+            .and_then(|diet| serde_json::from_str(&diet).ok())
+            .unwrap_or_default(),
+    })
+}
+
+fn diet_json(diet: &Diet) -> Result<String, ServiceError> {
+    serde_json::to_string(diet).map_err(|_| ServiceError::InternalError)
+}
+
+/// Raises an id counter to at least `id`, so that inserted ids are not reused.
+async fn raise_counter(
+    conn: &mut redis::aio::Connection,
+    key: &str,
+    id: usize,
+) -> Result<(), ServiceError> {
+    let current: Option<usize> = conn.get(key).await?;
+    if current.is_none_or(|current| current < id) {
+        conn.set::<_, _, ()>(key, id).await?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Store for RedisStore {
+    async fn user_id(&self, username: &str) -> Result<Option<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        Ok(conn.hget("users", username).await?)
+    }
+
+    async fn user(&self, id: usize) -> Result<Option<User>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let fields = conn
+            .hget(format!("user:{}", id), &["username", "password", "diet"])
+            .await?;
+        Ok(user_from_fields(id, fields))
+    }
+
+    async fn create_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let exists: bool = conn.hexists("users", username).await?;
+        if exists {
+            return Err(ServiceError::UserAlreadyExists {
+                username: username.to_string(),
+            });
+        }
+
+        let user_id: usize = conn.incr("next_user_id", 1_usize).await?;
+        redis::pipe()
+            .hset("users", username, user_id)
+            .hset_multiple(
+                format!("user:{}", user_id),
+                &[("username", username), ("password", password_hash)],
+            )
+            .query_async::<_, ()>(conn.deref_mut())
+            .await?;
+        Ok(user_id)
+    }
+
+    async fn set_password(&self, id: usize, password_hash: &str) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let key = format!("user:{}", id);
+        let exists: bool = conn.exists(&key).await?;
+        if exists {
+            conn.hset::<_, _, _, ()>(&key, "password", password_hash)
+                .await?;
+        }
+        Ok(exists)
+    }
+
+    async fn set_diet(&self, id: usize, diet: &Diet) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let key = format!("user:{}", id);
+        let exists: bool = conn.exists(&key).await?;
+        if exists {
+            conn.hset::<_, _, _, ()>(&key, "diet", diet_json(diet)?)
+                .await?;
+        }
+        Ok(exists)
+    }
+
+    async fn delete_user(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let username: Option<String> = conn.hget(format!("user:{}", id), "username").await?;
+        let username = match username {
+            Some(username) => username,
+            None => return Ok(false),
+        };
+
+        let list_ids: Vec<usize> = conn.zrange("dates", 0, -1).await?;
+        let mut pipe = redis::pipe();
+        for list_id in &list_ids {
+            pipe.sismember(format!("users:{}", list_id), id);
+        }
+        let attended: Vec<bool> = pipe.query_async(conn.deref_mut()).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .hdel("users", username)
+            .ignore()
+            .del(format!("user:{}", id))
+            .ignore()
+            .del(format!("refresh_tokens:{}", id))
+            .ignore();
+        for list_id in &list_ids {
+            pipe.srem(format!("users:{}", list_id), id)
+                .ignore()
+                .lrem(format!("waitlist:{}", list_id), 0, id)
+                .ignore()
+                .hdel(format!("guests:{}", list_id), id)
+                .ignore()
+                .hdel(format!("notes:{}", list_id), id)
+                .ignore()
+                .hdel(format!("answers:{}", list_id), id)
+                .ignore();
+        }
+        pipe.query_async::<_, ()>(conn.deref_mut()).await?;
+        for (list_id, attended) in list_ids.into_iter().zip(attended) {
+            if attended {
+                promote_waitlist(conn.deref_mut(), list_id).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn users(&self) -> Result<Vec<User>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let ids: Vec<usize> = conn.hvals("users").await?;
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.hget(format!("user:{}", id), &["username", "password", "diet"]);
+        }
+        let fields: Vec<UserFields> = pipe.query_async(conn.deref_mut()).await?;
+        Ok(ids
+            .into_iter()
+            .zip(fields)
+            .filter_map(|(id, fields)| user_from_fields(id, fields))
+            .collect())
+    }
+
+    async fn insert_user(&self, user: &User) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        redis::pipe()
+            .hset("users", &user.username, user.id)
+            .hset_multiple(
+                format!("user:{}", user.id),
+                &[
+                    ("username", &user.username),
+                    ("password", &user.password),
+                    ("diet", &diet_json(&user.diet)?),
+                ],
+            )
+            .query_async::<_, ()>(conn.deref_mut())
+            .await?;
+        raise_counter(&mut conn, "next_user_id", user.id).await
+    }
+
+    async fn add_refresh_token(&self, user_id: usize, digest: &[u8]) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.sadd(format!("refresh_tokens:{}", user_id), digest)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn remove_refresh_token(
+        &self,
+        user_id: usize,
+        digest: &[u8],
+    ) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.srem(format!("refresh_tokens:{}", user_id), digest)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn clear_refresh_tokens(&self, user_id: usize) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.del(format!("refresh_tokens:{}", user_id))
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn feed_secret(&self, user_id: usize) -> Result<Option<Vec<u8>>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.hget(format!("user:{}", user_id), "feed_secret")
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn set_feed_secret(&self, user_id: usize, secret: &[u8]) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let key = format!("user:{}", user_id);
+        let exists: bool = conn.exists(&key).await?;
+        if exists {
+            conn.hset::<_, _, _, ()>(&key, "feed_secret", secret)
+                .await?;
+        }
+        Ok(exists)
+    }
+
+    async fn list(&self, id: usize) -> Result<Option<List>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash = conn.hgetall(format!("list:{}", id)).await?;
+        Ok(List::from_hash(id, hash))
+    }
+
+    async fn list_ids(
+        &self,
+        start: usize,
+        stop: usize,
+        rev: bool,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let ids = if rev {
+            conn.zrange("dates", start as isize, stop as isize).await?
+        } else {
+            conn.zrevrange("dates", start as isize, stop as isize)
+                .await?
+        };
+        Ok(ids)
+    }
+
+    async fn list_ids_on(&self, date: NaiveDate) -> Result<Vec<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let days = date.num_days_from_ce();
+        conn.zrangebyscore("dates", days, days)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn list_ids_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        list_type: Option<&ListType>,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let score = |date: Option<NaiveDate>, unlimited: &str| {
+            date.map_or(unlimited.to_string(), |date| {
+                date.num_days_from_ce().to_string()
+            })
+        };
+        let (min, max) = (score(start, "-inf"), score(end, "+inf"));
+        let list_type = match list_type {
+            Some(list_type) => list_type,
+            None => return Ok(conn.zrangebyscore("dates", min, max).await?),
+        };
+
+        // There is at most one list per date and type, which is looked up in
+        // the list index for every date with a list.
+        let mut days: Vec<(usize, i32)> = conn.zrangebyscore_withscores("dates", min, max).await?;
+        days.dedup_by_key(|(_, day)| *day);
+        let fields: Vec<String> = days
+            .into_iter()
+            .filter_map(|(_, day)| NaiveDate::from_num_days_from_ce_opt(day))
+            .map(|date| date_type_field(date, list_type))
+            .collect();
+        if fields.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<Option<usize>> = redis::cmd("HMGET")
+            .arg("list_index")
+            .arg(fields)
+            .query_async(conn.deref_mut())
+            .await?;
+        Ok(ids.into_iter().flatten().collect())
+    }
+
+    async fn create_list(&self, list: &List) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let (reserved, id): (bool, usize) = redis::Script::new(RESERVE_LIST_SCRIPT)
+            .key("list_index")
+            .key("next_list_id")
+            .arg(index_field(list))
+            .invoke_async(conn.deref_mut())
+            .await?;
+        if !reserved {
+            return Err(ServiceError::ListAlreadyExists { id });
+        }
+
+        let mut pipe = redis::pipe();
+        write_list(&mut pipe, id, list);
+        pipe.query_async::<_, ()>(conn.deref_mut()).await?;
+        Ok(id)
+    }
+
+    async fn insert_list(&self, list: &List) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let mut pipe = redis::pipe();
+        write_list(&mut pipe, list.id, list);
+        pipe.hset("list_index", index_field(list), list.id)
+            .ignore()
+            .query_async::<_, ()>(conn.deref_mut())
+            .await?;
+        raise_counter(&mut conn, "next_list_id", list.id).await
+    }
+
+    async fn update_list(&self, list: &List) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash = conn.hgetall(format!("list:{}", list.id)).await?;
+        let old_field = match List::from_hash(list.id, hash) {
+            Some(old) => index_field(&old),
+            None => return Ok(false),
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        let field = index_field(list);
+        if field != old_field {
+            let reserved: bool = conn.hset_nx("list_index", &field, list.id).await?;
+            if !reserved {
+                let existing: usize = conn.hget("list_index", &field).await?;
+                if existing != list.id {
+                    return Err(ServiceError::ListAlreadyExists { id: existing });
+                }
+            }
+            pipe.hdel("list_index", old_field).ignore();
+        }
+        write_list(&mut pipe, list.id, list);
+        pipe.query_async::<_, ()>(conn.deref_mut()).await?;
+        promote_waitlist(conn.deref_mut(), list.id).await?;
+        Ok(true)
+    }
+
+    async fn delete_list(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash = conn.hgetall(format!("list:{}", id)).await?;
+        let list = match List::from_hash(id, hash) {
+            Some(list) => list,
+            None => return Ok(false),
+        };
+        redis::pipe()
+            .atomic()
+            .zrem("dates", id)
+            .ignore()
+            .hdel("list_index", index_field(&list))
+            .ignore()
+            .del(format!("users:{}", id))
+            .ignore()
+            .del(format!("waitlist:{}", id))
+            .ignore()
+            .del(format!("guests:{}", id))
+            .ignore()
+            .del(format!("notes:{}", id))
+            .ignore()
+            .del(format!("answers:{}", id))
+            .ignore()
+            .del(format!("list:{}", id))
+            .ignore()
+            .query_async::<_, ()>(conn.deref_mut())
+            .await?;
+        Ok(true)
+    }
+
+    async fn attendees(&self, list_id: usize) -> Result<HashSet<String>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let ids: Vec<usize> = conn.smembers(format!("users:{}", list_id)).await?;
+        let mut pipe = redis::pipe();
+        for id in ids {
+            pipe.hget(format!("user:{}", id), "username");
+        }
+        let names: Vec<Option<String>> = pipe.query_async(conn.deref_mut()).await?;
+        Ok(names.into_iter().flatten().collect())
+    }
+
+    async fn attendee_ids(&self, list_id: usize) -> Result<HashSet<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.smembers(format!("users:{}", list_id))
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn attendee_count(&self, list_id: usize) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.scard(format!("users:{}", list_id))
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn waitlist(&self, list_id: usize) -> Result<Vec<String>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let ids: Vec<usize> = conn.lrange(format!("waitlist:{}", list_id), 0, -1).await?;
+        let mut pipe = redis::pipe();
+        for id in ids {
+            pipe.hget(format!("user:{}", id), "username");
+        }
+        let names: Vec<Option<String>> = pipe.query_async(conn.deref_mut()).await?;
+        Ok(names.into_iter().flatten().collect())
+    }
+
+    async fn waitlist_ids(&self, list_id: usize) -> Result<Vec<usize>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.lrange(format!("waitlist:{}", list_id), 0, -1)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn guests(&self, list_id: usize) -> Result<HashMap<usize, Guests>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash: HashMap<usize, String> = conn.hgetall(format!("guests:{}", list_id)).await?;
+        hash.into_iter()
+            .map(|(user_id, guests)| {
+                let guests =
+                    serde_json::from_str(&guests).map_err(|_| ServiceError::InternalError)?;
+                Ok((user_id, guests))
+            })
+            .collect()
+    }
+
+    async fn notes(&self, list_id: usize) -> Result<HashMap<usize, String>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.hgetall(format!("notes:{}", list_id))
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn answers(&self, list_id: usize) -> Result<HashMap<usize, Rsvp>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash: HashMap<usize, String> = conn.hgetall(format!("answers:{}", list_id)).await?;
+        hash.into_iter()
+            .map(|(user_id, answer)| {
+                let answer = answer.parse().map_err(|_| ServiceError::InternalError)?;
+                Ok((user_id, answer))
+            })
+            .collect()
+    }
+
+    async fn set_answer(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        answer: Option<Rsvp>,
+    ) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let key = format!("answers:{}", list_id);
+        match answer {
+            Some(answer) => conn.hset(key, user_id, answer.to_string()).await?,
+            None => conn.hdel(key, user_id).await?,
+        }
+        Ok(())
+    }
+
+    async fn add_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+    ) -> Result<Attendance, ServiceError> {
+        let guests = if guests.count > 0 {
+            serde_json::to_string(guests).map_err(|_| ServiceError::InternalError)?
+        } else {
+            String::new()
+        };
+        let mut conn = self.pool.get().await?;
+        let result: usize = redis::Script::new(ADD_ATTENDEE_SCRIPT)
+            .key(format!("users:{}", list_id))
+            .key(format!("waitlist:{}", list_id))
+            .key(format!("list:{}", list_id))
+            .key(format!("guests:{}", list_id))
+            .key(format!("notes:{}", list_id))
+            .arg(user_id)
+            .arg(guests)
+            .arg(note.unwrap_or_default())
+            .invoke_async(conn.deref_mut())
+            .await?;
+        match result {
+            1 => Ok(Attendance::Joined),
+            2 => Ok(Attendance::Waitlisted),
+            3 => Err(ServiceError::NotEnoughSeats),
+            _ => Ok(Attendance::Unchanged),
+        }
+    }
+
+    async fn remove_attendee(&self, list_id: usize, user_id: usize) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        redis::Script::new(REMOVE_ATTENDEE_SCRIPT)
+            .key(format!("users:{}", list_id))
+            .key(format!("waitlist:{}", list_id))
+            .key(format!("list:{}", list_id))
+            .key(format!("guests:{}", list_id))
+            .key(format!("notes:{}", list_id))
+            .arg(user_id)
+            .invoke_async(conn.deref_mut())
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn insert_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+        waitlisted: bool,
+    ) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if guests.count > 0 {
+            let guests = serde_json::to_string(guests).map_err(|_| ServiceError::InternalError)?;
+            pipe.hset(format!("guests:{}", list_id), user_id, guests)
+                .ignore();
+        }
+        if let Some(note) = note {
+            pipe.hset(format!("notes:{}", list_id), user_id, note)
+                .ignore();
+        }
+        if waitlisted {
+            pipe.rpush(format!("waitlist:{}", list_id), user_id)
+                .ignore();
+        } else {
+            pipe.sadd(format!("users:{}", list_id), user_id).ignore();
+        }
+        pipe.query_async::<_, ()>(conn.deref_mut()).await?;
+        Ok(())
+    }
+
+    async fn templates(&self) -> Result<Vec<Template>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash: HashMap<usize, String> = conn.hgetall("templates").await?;
+        let generated: HashMap<usize, String> = conn.hgetall("templates_generated").await?;
+        let mut templates = hash
+            .into_iter()
+            .map(|(id, json)| {
+                let template: Template =
+                    serde_json::from_str(&json).map_err(|_| ServiceError::InternalError)?;
+                let generated_until = generated
+                    .get(&id)
+                    .map(|date| date.parse().map_err(|_| ServiceError::InternalError))
+                    .transpose()?;
+                Ok(Template {
+                    id,
+                    generated_until,
+                    ..template
+                })
+            })
+            .collect::<Result<Vec<_>, ServiceError>>()?;
+        templates.sort_by_key(|template| template.id);
+        Ok(templates)
+    }
+
+    async fn create_template(&self, template: &Template) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let id: usize = conn.incr("next_template_id", 1_usize).await?;
+        let json = serde_json::to_string(&Template {
+            generated_until: None,
+            ..template.clone()
+        })
+        .map_err(|_| ServiceError::InternalError)?;
+        let mut pipe = redis::pipe();
+        pipe.atomic().hset("templates", id, json).ignore();
+        if let Some(date) = template.generated_until {
+            pipe.hset("templates_generated", id, date.to_string())
+                .ignore();
+        }
+        pipe.query_async::<_, ()>(conn.deref_mut()).await?;
+        Ok(id)
+    }
+
+    async fn delete_template(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let (deleted, _): (usize, usize) = redis::pipe()
+            .atomic()
+            .hdel("templates", id)
+            .hdel("templates_generated", id)
+            .query_async(conn.deref_mut())
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn set_generated_until(&self, id: usize, date: NaiveDate) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        // Only existing templates are updated, a template may be deleted
+        // while its lists are created.
+        if conn.hexists("templates", id).await? {
+            conn.hset::<_, _, _, ()>("templates_generated", id, date.to_string())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn holidays(&self) -> Result<Vec<Holiday>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash: HashMap<String, String> = conn.hgetall("holidays").await?;
+        let mut holidays = hash
+            .into_iter()
+            .map(|(date, name)| {
+                let date = date.parse().map_err(|_| ServiceError::InternalError)?;
+                Ok(Holiday { date, name })
+            })
+            .collect::<Result<Vec<_>, ServiceError>>()?;
+        holidays.sort_by_key(|holiday| holiday.date);
+        Ok(holidays)
+    }
+
+    async fn holiday(&self, date: NaiveDate) -> Result<Option<Holiday>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let name: Option<String> = conn.hget("holidays", date.to_string()).await?;
+        Ok(name.map(|name| Holiday { date, name }))
+    }
+
+    async fn add_holiday(&self, holiday: &Holiday) -> Result<(), ServiceError> {
+        let mut conn = self.pool.get().await?;
+        conn.hset("holidays", holiday.date.to_string(), &holiday.name)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn delete_holiday(&self, date: NaiveDate) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let deleted: usize = conn.hdel("holidays", date.to_string()).await?;
+        Ok(deleted > 0)
+    }
+
+    async fn transactions(&self) -> Result<Vec<Transaction>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let json: Vec<String> = conn.lrange("transactions", 0, -1).await?;
+        json.iter()
+            .map(|json| serde_json::from_str(json).map_err(|_| ServiceError::InternalError))
+            .collect()
+    }
+
+    async fn add_transaction(&self, transaction: &Transaction) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let id: usize = conn.incr("next_transaction_id", 1_usize).await?;
+        let transaction = Transaction {
+            id,
+            ..transaction.clone()
+        };
+        let json = serde_json::to_string(&transaction).map_err(|_| ServiceError::InternalError)?;
+        conn.rpush::<_, _, ()>("transactions", json).await?;
+        Ok(id)
+    }
+
+    async fn recipes(&self) -> Result<Vec<Recipe>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let hash: HashMap<usize, String> = conn.hgetall("recipes").await?;
+        let mut recipes = hash
+            .into_iter()
+            .map(|(id, json)| {
+                let recipe: Recipe =
+                    serde_json::from_str(&json).map_err(|_| ServiceError::InternalError)?;
+                Ok(Recipe { id, ..recipe })
+            })
+            .collect::<Result<Vec<_>, ServiceError>>()?;
+        recipes.sort_by_key(|recipe| recipe.id);
+        Ok(recipes)
+    }
+
+    async fn recipe(&self, id: usize) -> Result<Option<Recipe>, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let json: Option<String> = conn.hget("recipes", id).await?;
+        json.map(|json| {
+            let recipe: Recipe =
+                serde_json::from_str(&json).map_err(|_| ServiceError::InternalError)?;
+            Ok(Recipe { id, ..recipe })
+        })
+        .transpose()
+    }
+
+    async fn create_recipe(&self, recipe: &Recipe) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let id: usize = conn.incr("next_recipe_id", 1_usize).await?;
+        let json = serde_json::to_string(recipe).map_err(|_| ServiceError::InternalError)?;
+        conn.hset::<_, _, _, ()>("recipes", id, json).await?;
+        Ok(id)
+    }
+
+    async fn delete_recipe(&self, id: usize) -> Result<bool, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let list_ids: Vec<usize> = conn.zrange("dates", 0, -1).await?;
+        let mut pipe = redis::pipe();
+        for list_id in &list_ids {
+            pipe.hget(format!("list:{}", list_id), "recipe");
+        }
+        let recipes: Vec<Option<usize>> = pipe.query_async(conn.deref_mut()).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().hdel("recipes", id);
+        for (list_id, recipe) in list_ids.iter().zip(recipes) {
+            if recipe == Some(id) {
+                pipe.hdel(format!("list:{}", list_id), "recipe").ignore();
+            }
+        }
+        let (deleted,): (usize,) = pipe.query_async(conn.deref_mut()).await?;
+        Ok(deleted > 0)
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        let version = redis_migrations::current_version(&mut conn).await?;
+        Ok(redis_migrations::SCHEMA_VERSION.saturating_sub(version))
+    }
+
+    async fn migrate(&self) -> Result<usize, ServiceError> {
+        let mut conn = self.pool.get().await?;
+        redis_migrations::migrate(&mut conn).await
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// Returns a pool for the database `db` of the Redis server at
+    /// `REDIS_URL`, or on localhost, after removing all of its keys.
+    pub async fn test_pool(db: u8) -> Pool {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1".to_string());
+        let client = redis::Client::open(format!("{}/{}", url.trim_end_matches('/'), db)).unwrap();
+        let pool = Pool::new(RedisConnectionManager::new(client));
+        let mut conn = pool.get().await.unwrap();
+        redis::cmd("FLUSHDB")
+            .query_async::<_, ()>(conn.deref_mut())
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[actix_rt::test]
+    #[ignore = "requires a Redis server"]
+    async fn test_list_ids_between() {
+        let store = RedisStore::new(test_pool(13).await);
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let lunch = List::new(0, "lunch".parse().unwrap(), date);
+        let dinner = List::new(0, "dinner".parse().unwrap(), date);
+        let later = List::new(0, "lunch".parse().unwrap(), date.succ_opt().unwrap());
+        let mut ids = Vec::new();
+        for list in &[lunch, dinner, later] {
+            ids.push(store.create_list(list).await.unwrap());
+        }
+
+        assert_eq!(store.list_ids_between(None, None, None).await.unwrap(), ids);
+        assert_eq!(
+            store
+                .list_ids_between(Some(date), None, Some(&"lunch".parse().unwrap()))
+                .await
+                .unwrap(),
+            vec![ids[0], ids[2]]
+        );
+        assert_eq!(
+            store
+                .list_ids_between(None, Some(date), Some(&"dinner".parse().unwrap()))
+                .await
+                .unwrap(),
+            vec![ids[1]]
+        );
+    }
+
+    #[actix_rt::test]
+    #[ignore = "requires a Redis server"]
+    async fn test_create_list_conflict_keeps_ids() {
+        let store = RedisStore::new(test_pool(12).await);
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let list = List::new(0, "lunch".parse().unwrap(), date);
+        let id = store.create_list(&list).await.unwrap();
+
+        match store.create_list(&list).await {
+            Err(ServiceError::ListAlreadyExists { id: existing }) => assert_eq!(existing, id),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let next = List::new(0, "dinner".parse().unwrap(), date);
+        assert_eq!(store.create_list(&next).await.unwrap(), id + 1);
+    }
+
+    #[actix_rt::test]
+    #[ignore = "requires a Redis server"]
+    async fn test_capacity_counts_guests() {
+        let store = RedisStore::new(test_pool(15).await);
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+        let third = store.create_user("Third User", "hash").await.unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let mut list = List::new(0, "lunch".parse().unwrap(), date);
+        list.capacity = Some(3);
+        let list_id = store.create_list(&list).await.unwrap();
+
+        let guests = |count| Guests {
+            count,
+            names: Vec::new(),
+        };
+        let attendance = store
+            .add_attendee(list_id, first, &guests(1), None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Joined);
+        let attendance = store
+            .add_attendee(list_id, second, &guests(2), Some("late"))
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Waitlisted);
+        let attendance = store
+            .add_attendee(list_id, third, &guests(0), None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Joined);
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![second]);
+
+        // Removing the first attendee frees two seats, not enough for three.
+        assert!(store.remove_attendee(list_id, first).await.unwrap());
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![second]);
+        assert!(store.remove_attendee(list_id, third).await.unwrap());
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+        assert!(store.attendee_ids(list_id).await.unwrap().contains(&second));
+        assert!(!store.remove_attendee(list_id, third).await.unwrap());
+
+        // An attendee cannot bring more guests than there are seats left.
+        let result = store.add_attendee(list_id, second, &guests(3), None).await;
+        assert!(matches!(result, Err(ServiceError::NotEnoughSeats)));
+        assert_eq!(store.guests(list_id).await.unwrap()[&second], guests(2));
+
+        // Bringing fewer guests frees seats for the waitlist.
+        store
+            .add_attendee(list_id, third, &guests(0), None)
+            .await
+            .unwrap();
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![third]);
+        store
+            .add_attendee(list_id, second, &guests(1), None)
+            .await
+            .unwrap();
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+
+        // Deleting an attendee promotes the waitlist as well.
+        list.id = list_id;
+        list.capacity = Some(2);
+        store.update_list(&list).await.unwrap();
+        store
+            .add_attendee(list_id, first, &guests(0), None)
+            .await
+            .unwrap();
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![first]);
+        assert!(store.delete_user(second).await.unwrap());
+        assert!(store.attendee_ids(list_id).await.unwrap().contains(&first));
+    }
+}
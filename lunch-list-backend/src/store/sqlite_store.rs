@@ -0,0 +1,1620 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::web;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rusqlite::{params, types::Type, Connection, OptionalExtension};
+
+use super::{Attendance, Store};
+use crate::{
+    errors::ServiceError,
+    holiday::Holiday,
+    ledger::Transaction,
+    list::{Guests, List, ListType, Rsvp},
+    recipe::Recipe,
+    template::Template,
+    user::{Diet, User},
+};
+
+/// Schema migrations, `PRAGMA user_version` holds the number of applied
+/// migrations. Only ever append to this list.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE users (
+        id INTEGER PRIMARY KEY,
+        username TEXT NOT NULL UNIQUE,
+        password TEXT NOT NULL
+    );
+    CREATE TABLE refresh_tokens (
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        digest BLOB NOT NULL,
+        PRIMARY KEY (user_id, digest)
+    );
+    CREATE TABLE lists (
+        id INTEGER PRIMARY KEY,
+        type TEXT NOT NULL,
+        date TEXT NOT NULL
+    );
+    CREATE INDEX lists_date ON lists(date);
+    CREATE TABLE attendees (
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user TEXT NOT NULL,
+        PRIMARY KEY (list_id, user)
+    );
+"#,
+    r#"
+    CREATE TABLE attendees_by_id (
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        PRIMARY KEY (list_id, user_id)
+    );
+    INSERT INTO attendees_by_id (list_id, user_id)
+        SELECT attendees.list_id, users.id FROM attendees
+        JOIN users ON users.username = attendees.user;
+    DROP TABLE attendees;
+    ALTER TABLE attendees_by_id RENAME TO attendees;
+"#,
+    r#"
+    DROP INDEX lists_date;
+    CREATE UNIQUE INDEX lists_date_type ON lists(date, type);
+"#,
+    r#"
+    ALTER TABLE lists ADD COLUMN deadline TEXT;
+"#,
+    r#"
+    ALTER TABLE lists ADD COLUMN capacity INTEGER;
+    CREATE TABLE waitlist (
+        position INTEGER PRIMARY KEY AUTOINCREMENT,
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        UNIQUE (list_id, user_id)
+    );
+"#,
+    r#"
+    CREATE TABLE guests (
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        count INTEGER NOT NULL,
+        names TEXT NOT NULL,
+        PRIMARY KEY (list_id, user_id)
+    );
+"#,
+    r#"
+    ALTER TABLE users ADD COLUMN vegetarian INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE users ADD COLUMN vegan INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE users ADD COLUMN gluten_free INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE users ADD COLUMN allergies TEXT;
+"#,
+    r#"
+    CREATE TABLE notes (
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        note TEXT NOT NULL,
+        PRIMARY KEY (list_id, user_id)
+    );
+"#,
+    r#"
+    CREATE TABLE answers (
+        list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        answer TEXT NOT NULL,
+        PRIMARY KEY (list_id, user_id)
+    );
+"#,
+    r#"
+    CREATE TABLE templates (
+        id INTEGER PRIMARY KEY,
+        type TEXT NOT NULL,
+        weekdays TEXT NOT NULL,
+        deadline TEXT,
+        capacity INTEGER
+    );
+"#,
+    r#"
+    CREATE TABLE holidays (
+        date TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+"#,
+    r#"
+    ALTER TABLE lists ADD COLUMN title TEXT;
+    ALTER TABLE lists ADD COLUMN menu TEXT;
+    ALTER TABLE lists ADD COLUMN cook TEXT;
+"#,
+    r#"
+    CREATE TABLE transactions (
+        id INTEGER PRIMARY KEY,
+        list_id INTEGER,
+        description TEXT NOT NULL,
+        created TEXT NOT NULL
+    );
+    CREATE TABLE transaction_amounts (
+        transaction_id INTEGER NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL,
+        amount INTEGER NOT NULL,
+        PRIMARY KEY (transaction_id, user_id)
+    );
+"#,
+    r#"
+    CREATE TABLE recipes (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        ingredients TEXT NOT NULL
+    );
+    ALTER TABLE lists ADD COLUMN recipe INTEGER;
+"#,
+    r#"
+    ALTER TABLE templates ADD COLUMN generated_until TEXT;
+"#,
+    r#"
+    CREATE TABLE users_autoincrement (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        username TEXT NOT NULL UNIQUE,
+        password TEXT NOT NULL,
+        vegetarian INTEGER NOT NULL DEFAULT 0,
+        vegan INTEGER NOT NULL DEFAULT 0,
+        gluten_free INTEGER NOT NULL DEFAULT 0,
+        allergies TEXT
+    );
+    INSERT INTO users_autoincrement
+        SELECT id, username, password, vegetarian, vegan, gluten_free, allergies FROM users;
+    DROP TABLE users;
+    ALTER TABLE users_autoincrement RENAME TO users;
+    CREATE TABLE lists_autoincrement (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        type TEXT NOT NULL,
+        date TEXT NOT NULL,
+        deadline TEXT,
+        capacity INTEGER,
+        title TEXT,
+        menu TEXT,
+        cook TEXT,
+        recipe INTEGER
+    );
+    INSERT INTO lists_autoincrement
+        SELECT id, type, date, deadline, capacity, title, menu, cook, recipe FROM lists;
+    DROP TABLE lists;
+    ALTER TABLE lists_autoincrement RENAME TO lists;
+    CREATE UNIQUE INDEX lists_date_type ON lists(date, type);
+"#,
+    r#"
+    CREATE TABLE transactions_keyed (
+        id INTEGER PRIMARY KEY,
+        list_id INTEGER REFERENCES lists(id) ON DELETE SET NULL,
+        description TEXT NOT NULL,
+        created TEXT NOT NULL
+    );
+    INSERT INTO transactions_keyed
+        SELECT id, (SELECT lists.id FROM lists WHERE lists.id = transactions.list_id),
+            description, created
+        FROM transactions;
+    CREATE TABLE transaction_amounts_keyed (
+        transaction_id INTEGER NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+        user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        amount INTEGER NOT NULL,
+        PRIMARY KEY (transaction_id, user_id)
+    );
+    INSERT INTO transaction_amounts_keyed
+        SELECT transaction_id, user_id, amount FROM transaction_amounts
+        WHERE user_id IN (SELECT id FROM users);
+    DROP TABLE transaction_amounts;
+    DROP TABLE transactions;
+    ALTER TABLE transactions_keyed RENAME TO transactions;
+    ALTER TABLE transaction_amounts_keyed RENAME TO transaction_amounts;
+"#,
+    r#"
+    ALTER TABLE users ADD COLUMN feed_secret BLOB;
+"#,
+];
+
+/// Store backed by a SQLite database file.
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens the database at the given path and migrates it to the latest schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, rusqlite::Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens a new database in memory.
+    pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, rusqlite::Error> {
+        // Migrations rebuild tables that others refer to, which would cascade
+        // deletes with foreign keys enabled.
+        conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        migrate(&mut conn)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs a query on the blocking thread pool.
+    async fn run<F, T>(&self, f: F) -> Result<T, ServiceError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, ServiceError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        web::block(move || {
+            let mut conn = conn.lock().map_err(|_| ServiceError::InternalError)?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(ServiceError::from)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), rusqlite::Error> {
+    let version: i64 = conn.query_row("PRAGMA user_version", params![], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Applying SQLite migration {}", i + 1);
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {};", i + 1))?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+const USER_COLUMNS: &str = "id, username, password, vegetarian, vegan, gluten_free, allergies";
+
+/// Reads a user from a row of `SELECT {USER_COLUMNS} FROM users`.
+fn user_from_row(row: &rusqlite::Row) -> Result<User, rusqlite::Error> {
+    Ok(User {
+        id: row.get::<_, i64>(0)? as usize,
+        username: row.get(1)?,
+        password: row.get(2)?,
+        diet: Diet {
+            vegetarian: row.get(3)?,
+            vegan: row.get(4)?,
+            gluten_free: row.get(5)?,
+            allergies: row.get(6)?,
+        },
+    })
+}
+
+/// Returns the number of seats a user takes on a list, for themselves and
+/// their guests.
+fn party_size(conn: &Connection, list_id: usize, user_id: usize) -> Result<usize, rusqlite::Error> {
+    conn.query_row(
+        "SELECT 1 + COALESCE((SELECT count FROM guests WHERE list_id = ? AND user_id = ?), 0)",
+        params![list_id as i64, user_id as i64],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|seats| seats as usize)
+}
+
+/// Returns true when a list has no capacity or a party of `seats` fits next
+/// to the attendees and their guests.
+fn has_seats(conn: &Connection, list_id: usize, seats: usize) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT capacity IS NULL OR capacity >= ?2 + (
+             SELECT COUNT(*) + COALESCE(SUM(guests.count), 0) FROM attendees
+             LEFT JOIN guests ON guests.list_id = attendees.list_id
+                 AND guests.user_id = attendees.user_id
+             WHERE attendees.list_id = ?1
+         )
+         FROM lists WHERE id = ?1",
+        params![list_id as i64, seats as i64],
+        |row| row.get(0),
+    )
+}
+
+/// Promotes users from the front of the waitlist of a list for as long as
+/// their party fits.
+fn promote_waitlist(conn: &Connection, list_id: usize) -> Result<(), rusqlite::Error> {
+    loop {
+        let next: Option<(i64, i64)> = conn
+            .query_row(
+                "SELECT position, user_id FROM waitlist
+                 WHERE list_id = ? ORDER BY position LIMIT 1",
+                params![list_id as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (position, next_id) = match next {
+            Some(next) => next,
+            None => return Ok(()),
+        };
+        if !has_seats(conn, list_id, party_size(conn, list_id, next_id as usize)?)? {
+            return Ok(());
+        }
+        conn.execute("DELETE FROM waitlist WHERE position = ?", params![position])?;
+        conn.execute(
+            "INSERT INTO attendees (list_id, user_id) VALUES (?, ?)",
+            params![list_id as i64, next_id],
+        )?;
+    }
+}
+
+/// Columns of the `lists` table read by `list_from_row`.
+const LIST_COLUMNS: &str = "id, type, date, deadline, capacity, title, menu, cook, recipe";
+
+/// Reads a list from a row of `LIST_COLUMNS`.
+fn list_from_row(row: &rusqlite::Row) -> Result<List, rusqlite::Error> {
+    let list_type = row
+        .get::<_, String>(1)?
+        .parse::<ListType>()
+        .map_err(|_| rusqlite::Error::InvalidColumnType(1, "type".into(), Type::Text))?;
+    let mut list = List::new(row.get::<_, i64>(0)? as usize, list_type, row.get(2)?);
+    list.deadline = row.get(3)?;
+    list.capacity = row
+        .get::<_, Option<i64>>(4)?
+        .map(|capacity| capacity as usize);
+    list.title = row.get(5)?;
+    list.menu = row.get(6)?;
+    list.cook = row.get(7)?;
+    list.recipe = row.get::<_, Option<i64>>(8)?.map(|recipe| recipe as usize);
+    Ok(list)
+}
+
+/// Reads a recipe whose ingredients are stored as JSON.
+fn recipe_from_row(id: usize, name: String, ingredients: &str) -> Result<Recipe, ServiceError> {
+    Ok(Recipe {
+        id,
+        name,
+        ingredients: serde_json::from_str(ingredients).map_err(|_| ServiceError::InternalError)?,
+    })
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn user_id(&self, username: &str) -> Result<Option<usize>, ServiceError> {
+        let username = username.to_string();
+        self.run(move |conn| {
+            conn.query_row(
+                "SELECT id FROM users WHERE username = ?",
+                params![username],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map(|id| id.map(|id| id as usize))
+            .map_err(ServiceError::from)
+        })
+        .await
+    }
+
+    async fn user(&self, id: usize) -> Result<Option<User>, ServiceError> {
+        self.run(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS),
+                params![id as i64],
+                user_from_row,
+            )
+            .optional()
+            .map_err(ServiceError::from)
+        })
+        .await
+    }
+
+    async fn create_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<usize, ServiceError> {
+        let username = username.to_string();
+        let password_hash = password_hash.to_string();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM users WHERE username = ?)",
+                params![username],
+                |row| row.get(0),
+            )?;
+            if exists {
+                return Err(ServiceError::UserAlreadyExists { username });
+            }
+
+            tx.execute(
+                "INSERT INTO users (username, password) VALUES (?, ?)",
+                params![username, password_hash],
+            )?;
+            let id = tx.last_insert_rowid() as usize;
+            tx.commit()?;
+            Ok(id)
+        })
+        .await
+    }
+
+    async fn set_password(&self, id: usize, password_hash: &str) -> Result<bool, ServiceError> {
+        let password_hash = password_hash.to_string();
+        self.run(move |conn| {
+            let updated = conn.execute(
+                "UPDATE users SET password = ? WHERE id = ?",
+                params![password_hash, id as i64],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn set_diet(&self, id: usize, diet: &Diet) -> Result<bool, ServiceError> {
+        let diet = diet.clone();
+        self.run(move |conn| {
+            let updated = conn.execute(
+                "UPDATE users SET vegetarian = ?, vegan = ?, gluten_free = ?, allergies = ?
+                 WHERE id = ?",
+                params![
+                    diet.vegetarian,
+                    diet.vegan,
+                    diet.gluten_free,
+                    diet.allergies,
+                    id as i64
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn delete_user(&self, id: usize) -> Result<bool, ServiceError> {
+        // Refresh tokens, attendance, waitlists, guests, notes and answers are
+        // removed by the foreign keys.
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let attended = tx
+                .prepare("SELECT list_id FROM attendees WHERE user_id = ?")?
+                .query_map(params![id as i64], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let deleted = tx.execute("DELETE FROM users WHERE id = ?", params![id as i64])?;
+            for list_id in attended {
+                promote_waitlist(&tx, list_id as usize)?;
+            }
+            tx.commit()?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn users(&self) -> Result<Vec<User>, ServiceError> {
+        self.run(|conn| {
+            let mut stmt = conn.prepare(&format!("SELECT {} FROM users", USER_COLUMNS))?;
+            let users = stmt
+                .query_map(params![], user_from_row)?
+                .collect::<Result<_, _>>()?;
+            Ok(users)
+        })
+        .await
+    }
+
+    async fn insert_user(&self, user: &User) -> Result<(), ServiceError> {
+        let user = user.clone();
+        self.run(move |conn| {
+            conn.execute(
+                &format!(
+                    "INSERT INTO users ({}) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    USER_COLUMNS
+                ),
+                params![
+                    user.id as i64,
+                    user.username,
+                    user.password,
+                    user.diet.vegetarian,
+                    user.diet.vegan,
+                    user.diet.gluten_free,
+                    user.diet.allergies
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn add_refresh_token(&self, user_id: usize, digest: &[u8]) -> Result<(), ServiceError> {
+        let digest = digest.to_vec();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO refresh_tokens (user_id, digest) VALUES (?, ?)",
+                params![user_id as i64, digest],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove_refresh_token(
+        &self,
+        user_id: usize,
+        digest: &[u8],
+    ) -> Result<bool, ServiceError> {
+        let digest = digest.to_vec();
+        self.run(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM refresh_tokens WHERE user_id = ? AND digest = ?",
+                params![user_id as i64, digest],
+            )?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    async fn clear_refresh_tokens(&self, user_id: usize) -> Result<(), ServiceError> {
+        self.run(move |conn| {
+            conn.execute(
+                "DELETE FROM refresh_tokens WHERE user_id = ?",
+                params![user_id as i64],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn feed_secret(&self, user_id: usize) -> Result<Option<Vec<u8>>, ServiceError> {
+        self.run(move |conn| {
+            let secret = conn
+                .query_row(
+                    "SELECT feed_secret FROM users WHERE id = ?",
+                    params![user_id as i64],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(secret.flatten())
+        })
+        .await
+    }
+
+    async fn set_feed_secret(&self, user_id: usize, secret: &[u8]) -> Result<bool, ServiceError> {
+        let secret = secret.to_vec();
+        self.run(move |conn| {
+            let updated = conn.execute(
+                "UPDATE users SET feed_secret = ? WHERE id = ?",
+                params![secret, user_id as i64],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn list(&self, id: usize) -> Result<Option<List>, ServiceError> {
+        self.run(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM lists WHERE id = ?", LIST_COLUMNS),
+                params![id as i64],
+                list_from_row,
+            )
+            .optional()
+            .map_err(ServiceError::from)
+        })
+        .await
+    }
+
+    async fn list_ids(
+        &self,
+        start: usize,
+        stop: usize,
+        rev: bool,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let query = if rev {
+            "SELECT id FROM lists ORDER BY date ASC, id ASC LIMIT ? OFFSET ?"
+        } else {
+            "SELECT id FROM lists ORDER BY date DESC, id DESC LIMIT ? OFFSET ?"
+        };
+        let len = (stop + 1).saturating_sub(start);
+        self.run(move |conn| {
+            let mut stmt = conn.prepare(query)?;
+            let ids = stmt
+                .query_map(params![len as i64, start as i64], |row| {
+                    row.get::<_, i64>(0)
+                })?
+                .map(|id| id.map(|id| id as usize))
+                .collect::<Result<_, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn list_ids_on(&self, date: NaiveDate) -> Result<Vec<usize>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare("SELECT id FROM lists WHERE date = ?")?;
+            let ids = stmt
+                .query_map(params![date], |row| row.get::<_, i64>(0))?
+                .map(|id| id.map(|id| id as usize))
+                .collect::<Result<_, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn list_ids_between(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        list_type: Option<&ListType>,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let list_type = list_type.map(ListType::to_string);
+        self.run(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id FROM lists WHERE (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR date <= ?2)
+                     AND (?3 IS NULL OR type = ?3)
+                 ORDER BY date ASC, id ASC",
+            )?;
+            let ids = stmt
+                .query_map(params![start, end, list_type], |row| {
+                    row.get::<_, i64>(0)
+                })?
+                .map(|id| id.map(|id| id as usize))
+                .collect::<Result<_, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn create_list(&self, list: &List) -> Result<usize, ServiceError> {
+        let list = list.clone();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let existing: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM lists WHERE date = ? AND type = ?",
+                    params![list.date, list.list_type.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(id) = existing {
+                return Err(ServiceError::ListAlreadyExists { id: id as usize });
+            }
+
+            tx.execute(
+                "INSERT INTO lists (type, date, deadline, capacity, title, menu, cook, recipe)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    list.list_type.to_string(),
+                    list.date,
+                    list.deadline,
+                    list.capacity.map(|capacity| capacity as i64),
+                    list.title,
+                    list.menu,
+                    list.cook,
+                    list.recipe.map(|recipe| recipe as i64)
+                ],
+            )?;
+            let id = tx.last_insert_rowid() as usize;
+            tx.commit()?;
+            Ok(id)
+        })
+        .await
+    }
+
+    async fn insert_list(&self, list: &List) -> Result<(), ServiceError> {
+        let list = list.clone();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO lists (id, type, date, deadline, capacity, title, menu, cook,
+                 recipe) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    list.id as i64,
+                    list.list_type.to_string(),
+                    list.date,
+                    list.deadline,
+                    list.capacity.map(|capacity| capacity as i64),
+                    list.title,
+                    list.menu,
+                    list.cook,
+                    list.recipe.map(|recipe| recipe as i64)
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_list(&self, list: &List) -> Result<bool, ServiceError> {
+        let list = list.clone();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let existing: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM lists WHERE date = ? AND type = ? AND id != ?",
+                    params![list.date, list.list_type.to_string(), list.id as i64],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(id) = existing {
+                return Err(ServiceError::ListAlreadyExists { id: id as usize });
+            }
+
+            let updated = tx.execute(
+                "UPDATE lists SET type = ?, date = ?, deadline = ?, capacity = ?, title = ?,
+                 menu = ?, cook = ?, recipe = ? WHERE id = ?",
+                params![
+                    list.list_type.to_string(),
+                    list.date,
+                    list.deadline,
+                    list.capacity.map(|capacity| capacity as i64),
+                    list.title,
+                    list.menu,
+                    list.cook,
+                    list.recipe.map(|recipe| recipe as i64),
+                    list.id as i64
+                ],
+            )?;
+            if updated > 0 {
+                promote_waitlist(&tx, list.id)?;
+            }
+            tx.commit()?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn delete_list(&self, id: usize) -> Result<bool, ServiceError> {
+        self.run(move |conn| {
+            let deleted = conn.execute("DELETE FROM lists WHERE id = ?", params![id as i64])?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn attendees(&self, list_id: usize) -> Result<HashSet<String>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT users.username FROM attendees
+                 JOIN users ON users.id = attendees.user_id
+                 WHERE attendees.list_id = ?",
+            )?;
+            let users = stmt
+                .query_map(params![list_id as i64], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            Ok(users)
+        })
+        .await
+    }
+
+    async fn attendee_ids(&self, list_id: usize) -> Result<HashSet<usize>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare("SELECT user_id FROM attendees WHERE list_id = ?")?;
+            let ids = stmt
+                .query_map(params![list_id as i64], |row| row.get::<_, i64>(0))?
+                .map(|id| id.map(|id| id as usize))
+                .collect::<Result<_, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn attendee_count(&self, list_id: usize) -> Result<usize, ServiceError> {
+        self.run(move |conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM attendees WHERE list_id = ?",
+                params![list_id as i64],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as usize)
+            .map_err(ServiceError::from)
+        })
+        .await
+    }
+
+    async fn waitlist(&self, list_id: usize) -> Result<Vec<String>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT users.username FROM waitlist
+                 JOIN users ON users.id = waitlist.user_id
+                 WHERE waitlist.list_id = ? ORDER BY waitlist.position",
+            )?;
+            let users = stmt
+                .query_map(params![list_id as i64], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            Ok(users)
+        })
+        .await
+    }
+
+    async fn waitlist_ids(&self, list_id: usize) -> Result<Vec<usize>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT user_id FROM waitlist WHERE list_id = ? ORDER BY position")?;
+            let ids = stmt
+                .query_map(params![list_id as i64], |row| row.get::<_, i64>(0))?
+                .map(|id| id.map(|id| id as usize))
+                .collect::<Result<_, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn guests(&self, list_id: usize) -> Result<HashMap<usize, Guests>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT user_id, count, names FROM guests WHERE list_id = ?")?;
+            let rows = stmt
+                .query_map(params![list_id as i64], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, i64>(1)? as usize,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(|(user_id, count, names)| {
+                    let names =
+                        serde_json::from_str(&names).map_err(|_| ServiceError::InternalError)?;
+                    Ok((user_id, Guests { count, names }))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn notes(&self, list_id: usize) -> Result<HashMap<usize, String>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare("SELECT user_id, note FROM notes WHERE list_id = ?")?;
+            let notes = stmt
+                .query_map(params![list_id as i64], |row| {
+                    Ok((row.get::<_, i64>(0)? as usize, row.get(1)?))
+                })?
+                .collect::<Result<_, _>>()?;
+            Ok(notes)
+        })
+        .await
+    }
+
+    async fn answers(&self, list_id: usize) -> Result<HashMap<usize, Rsvp>, ServiceError> {
+        self.run(move |conn| {
+            let mut stmt = conn.prepare("SELECT user_id, answer FROM answers WHERE list_id = ?")?;
+            let rows = stmt
+                .query_map(params![list_id as i64], |row| {
+                    Ok((row.get::<_, i64>(0)? as usize, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(|(user_id, answer)| {
+                    let answer = answer.parse().map_err(|_| ServiceError::InternalError)?;
+                    Ok((user_id, answer))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn set_answer(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        answer: Option<Rsvp>,
+    ) -> Result<(), ServiceError> {
+        self.run(move |conn| {
+            match answer {
+                Some(answer) => conn.execute(
+                    "INSERT OR REPLACE INTO answers (list_id, user_id, answer) VALUES (?, ?, ?)",
+                    params![list_id as i64, user_id as i64, answer.to_string()],
+                )?,
+                None => conn.execute(
+                    "DELETE FROM answers WHERE list_id = ? AND user_id = ?",
+                    params![list_id as i64, user_id as i64],
+                )?,
+            };
+            Ok(())
+        })
+        .await
+    }
+
+    async fn add_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+    ) -> Result<Attendance, ServiceError> {
+        let count = guests.count as i64;
+        let names =
+            serde_json::to_string(&guests.names).map_err(|_| ServiceError::InternalError)?;
+        let note = note.map(str::to_string);
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let attending: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM attendees WHERE list_id = ? AND user_id = ?)",
+                params![list_id as i64, user_id as i64],
+                |row| row.get(0),
+            )?;
+            let party = 1 + count as usize;
+            let previous = party_size(&tx, list_id, user_id)?;
+            if attending && party > previous && !has_seats(&tx, list_id, party - previous)? {
+                return Err(ServiceError::NotEnoughSeats);
+            }
+
+            match note {
+                Some(note) => tx.execute(
+                    "INSERT OR REPLACE INTO notes (list_id, user_id, note) VALUES (?, ?, ?)",
+                    params![list_id as i64, user_id as i64, note],
+                )?,
+                None => tx.execute(
+                    "DELETE FROM notes WHERE list_id = ? AND user_id = ?",
+                    params![list_id as i64, user_id as i64],
+                )?,
+            };
+            if count > 0 {
+                tx.execute(
+                    "INSERT OR REPLACE INTO guests (list_id, user_id, count, names)
+                     VALUES (?, ?, ?, ?)",
+                    params![list_id as i64, user_id as i64, count, names],
+                )?;
+            } else {
+                tx.execute(
+                    "DELETE FROM guests WHERE list_id = ? AND user_id = ?",
+                    params![list_id as i64, user_id as i64],
+                )?;
+            }
+
+            let waitlisted: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM waitlist WHERE list_id = ? AND user_id = ?)",
+                params![list_id as i64, user_id as i64],
+                |row| row.get(0),
+            )?;
+            if attending || waitlisted {
+                if party < previous {
+                    promote_waitlist(&tx, list_id)?;
+                }
+                let promoted = waitlisted
+                    && !tx.query_row(
+                        "SELECT EXISTS(SELECT 1 FROM waitlist WHERE list_id = ? AND user_id = ?)",
+                        params![list_id as i64, user_id as i64],
+                        |row| row.get::<_, bool>(0),
+                    )?;
+                tx.commit()?;
+                return Ok(if promoted {
+                    Attendance::Joined
+                } else {
+                    Attendance::Unchanged
+                });
+            }
+
+            let attendance = if has_seats(&tx, list_id, party_size(&tx, list_id, user_id)?)? {
+                tx.execute(
+                    "INSERT INTO attendees (list_id, user_id) VALUES (?, ?)",
+                    params![list_id as i64, user_id as i64],
+                )?;
+                Attendance::Joined
+            } else {
+                tx.execute(
+                    "INSERT INTO waitlist (list_id, user_id) VALUES (?, ?)",
+                    params![list_id as i64, user_id as i64],
+                )?;
+                Attendance::Waitlisted
+            };
+            tx.commit()?;
+            Ok(attendance)
+        })
+        .await
+    }
+
+    async fn remove_attendee(&self, list_id: usize, user_id: usize) -> Result<bool, ServiceError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            for table in &["guests", "notes"] {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE list_id = ? AND user_id = ?", table),
+                    params![list_id as i64, user_id as i64],
+                )?;
+            }
+            let removed = tx.execute(
+                "DELETE FROM attendees WHERE list_id = ? AND user_id = ?",
+                params![list_id as i64, user_id as i64],
+            )?;
+            if removed == 0 {
+                let removed = tx.execute(
+                    "DELETE FROM waitlist WHERE list_id = ? AND user_id = ?",
+                    params![list_id as i64, user_id as i64],
+                )?;
+                tx.commit()?;
+                return Ok(removed > 0);
+            }
+
+            promote_waitlist(&tx, list_id)?;
+            tx.commit()?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn insert_attendee(
+        &self,
+        list_id: usize,
+        user_id: usize,
+        guests: &Guests,
+        note: Option<&str>,
+        waitlisted: bool,
+    ) -> Result<(), ServiceError> {
+        let count = guests.count as i64;
+        let names =
+            serde_json::to_string(&guests.names).map_err(|_| ServiceError::InternalError)?;
+        let note = note.map(str::to_string);
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            if count > 0 {
+                tx.execute(
+                    "INSERT OR REPLACE INTO guests (list_id, user_id, count, names)
+                     VALUES (?, ?, ?, ?)",
+                    params![list_id as i64, user_id as i64, count, names],
+                )?;
+            }
+            if let Some(note) = note {
+                tx.execute(
+                    "INSERT OR REPLACE INTO notes (list_id, user_id, note) VALUES (?, ?, ?)",
+                    params![list_id as i64, user_id as i64, note],
+                )?;
+            }
+            let table = if waitlisted { "waitlist" } else { "attendees" };
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {} (list_id, user_id) VALUES (?, ?)",
+                    table
+                ),
+                params![list_id as i64, user_id as i64],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn templates(&self) -> Result<Vec<Template>, ServiceError> {
+        self.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, type, weekdays, deadline, capacity, generated_until FROM templates
+                 ORDER BY id",
+            )?;
+            let rows = stmt
+                .query_map(params![], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get(3)?,
+                        row.get::<_, Option<i64>>(4)?,
+                        row.get(5)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(
+                    |(id, list_type, weekdays, deadline, capacity, generated_until)| {
+                        Ok(Template {
+                            id,
+                            list_type: list_type
+                                .parse()
+                                .map_err(|_| ServiceError::InternalError)?,
+                            weekdays: serde_json::from_str(&weekdays)
+                                .map_err(|_| ServiceError::InternalError)?,
+                            deadline,
+                            capacity: capacity.map(|capacity| capacity as usize),
+                            generated_until,
+                        })
+                    },
+                )
+                .collect()
+        })
+        .await
+    }
+
+    async fn create_template(&self, template: &Template) -> Result<usize, ServiceError> {
+        let template = template.clone();
+        self.run(move |conn| {
+            let weekdays = serde_json::to_string(&template.weekdays)
+                .map_err(|_| ServiceError::InternalError)?;
+            conn.execute(
+                "INSERT INTO templates (type, weekdays, deadline, capacity, generated_until)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    template.list_type.to_string(),
+                    weekdays,
+                    template.deadline,
+                    template.capacity.map(|capacity| capacity as i64),
+                    template.generated_until
+                ],
+            )?;
+            Ok(conn.last_insert_rowid() as usize)
+        })
+        .await
+    }
+
+    async fn delete_template(&self, id: usize) -> Result<bool, ServiceError> {
+        self.run(move |conn| {
+            let deleted = conn.execute("DELETE FROM templates WHERE id = ?", params![id as i64])?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn set_generated_until(&self, id: usize, date: NaiveDate) -> Result<(), ServiceError> {
+        self.run(move |conn| {
+            conn.execute(
+                "UPDATE templates SET generated_until = ? WHERE id = ?",
+                params![date, id as i64],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn holidays(&self) -> Result<Vec<Holiday>, ServiceError> {
+        self.run(|conn| {
+            let mut stmt = conn.prepare("SELECT date, name FROM holidays ORDER BY date")?;
+            let holidays = stmt
+                .query_map(params![], |row| {
+                    Ok(Holiday {
+                        date: row.get(0)?,
+                        name: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<_, _>>()?;
+            Ok(holidays)
+        })
+        .await
+    }
+
+    async fn holiday(&self, date: NaiveDate) -> Result<Option<Holiday>, ServiceError> {
+        self.run(move |conn| {
+            conn.query_row(
+                "SELECT name FROM holidays WHERE date = ?",
+                params![date],
+                |row| {
+                    Ok(Holiday {
+                        date,
+                        name: row.get(0)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(ServiceError::from)
+        })
+        .await
+    }
+
+    async fn add_holiday(&self, holiday: &Holiday) -> Result<(), ServiceError> {
+        let holiday = holiday.clone();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO holidays (date, name) VALUES (?, ?)",
+                params![holiday.date, holiday.name],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_holiday(&self, date: NaiveDate) -> Result<bool, ServiceError> {
+        self.run(move |conn| {
+            let deleted = conn.execute("DELETE FROM holidays WHERE date = ?", params![date])?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn transactions(&self) -> Result<Vec<Transaction>, ServiceError> {
+        self.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, list_id, description, created FROM transactions ORDER BY id",
+            )?;
+            let mut transactions = stmt
+                .query_map(params![], |row| {
+                    Ok(Transaction {
+                        id: row.get::<_, i64>(0)? as usize,
+                        list_id: row.get::<_, Option<i64>>(1)?.map(|id| id as usize),
+                        description: row.get(2)?,
+                        created: row.get(3)?,
+                        amounts: Default::default(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut stmt =
+                conn.prepare("SELECT transaction_id, user_id, amount FROM transaction_amounts")?;
+            let amounts = stmt
+                .query_map(params![], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, i64>(1)? as usize,
+                        row.get::<_, i64>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let index: HashMap<usize, usize> = transactions
+                .iter()
+                .enumerate()
+                .map(|(index, transaction)| (transaction.id, index))
+                .collect();
+            for (transaction_id, user_id, amount) in amounts {
+                if let Some(index) = index.get(&transaction_id) {
+                    transactions[*index].amounts.insert(user_id, amount);
+                }
+            }
+            Ok(transactions)
+        })
+        .await
+    }
+
+    async fn add_transaction(&self, transaction: &Transaction) -> Result<usize, ServiceError> {
+        let transaction = transaction.clone();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            // References to deleted lists and users are dropped, as if they
+            // were deleted afterwards.
+            tx.execute(
+                "INSERT INTO transactions (list_id, description, created)
+                 VALUES ((SELECT id FROM lists WHERE id = ?), ?, ?)",
+                params![
+                    transaction.list_id.map(|id| id as i64),
+                    transaction.description,
+                    transaction.created
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            for (user_id, amount) in &transaction.amounts {
+                tx.execute(
+                    "INSERT INTO transaction_amounts (transaction_id, user_id, amount)
+                     SELECT ?1, id, ?3 FROM users WHERE id = ?2",
+                    params![id, *user_id as i64, amount],
+                )?;
+            }
+            tx.commit()?;
+            Ok(id as usize)
+        })
+        .await
+    }
+
+    async fn recipes(&self) -> Result<Vec<Recipe>, ServiceError> {
+        self.run(|conn| {
+            let mut stmt = conn.prepare("SELECT id, name, ingredients FROM recipes ORDER BY id")?;
+            let rows = stmt
+                .query_map(params![], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(|(id, name, ingredients)| recipe_from_row(id, name, &ingredients))
+                .collect()
+        })
+        .await
+    }
+
+    async fn recipe(&self, id: usize) -> Result<Option<Recipe>, ServiceError> {
+        self.run(move |conn| {
+            let row = conn
+                .query_row(
+                    "SELECT name, ingredients FROM recipes WHERE id = ?",
+                    params![id as i64],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()?;
+            row.map(|(name, ingredients)| recipe_from_row(id, name, &ingredients))
+                .transpose()
+        })
+        .await
+    }
+
+    async fn create_recipe(&self, recipe: &Recipe) -> Result<usize, ServiceError> {
+        let recipe = recipe.clone();
+        self.run(move |conn| {
+            let ingredients = serde_json::to_string(&recipe.ingredients)
+                .map_err(|_| ServiceError::InternalError)?;
+            conn.execute(
+                "INSERT INTO recipes (name, ingredients) VALUES (?, ?)",
+                params![recipe.name, ingredients],
+            )?;
+            Ok(conn.last_insert_rowid() as usize)
+        })
+        .await
+    }
+
+    async fn delete_recipe(&self, id: usize) -> Result<bool, ServiceError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE lists SET recipe = NULL WHERE recipe = ?",
+                params![id as i64],
+            )?;
+            let deleted = tx.execute("DELETE FROM recipes WHERE id = ?", params![id as i64])?;
+            tx.commit()?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, ServiceError> {
+        // The schema is migrated when the database is opened.
+        Ok(0)
+    }
+
+    async fn migrate(&self) -> Result<usize, ServiceError> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds a transaction on a list that moves `amount` from `from` to `to`.
+    async fn add_expense(
+        store: &SqliteStore,
+        list_id: usize,
+        from: usize,
+        to: usize,
+        amount: i64,
+    ) -> usize {
+        let mut transaction = Transaction::settlement(from, to, amount);
+        transaction.list_id = Some(list_id);
+        store.add_transaction(&transaction).await.unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_delete_user_clears_balance() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let list_id = store
+            .create_list(&List::new(0, "lunch".parse().unwrap(), date))
+            .await
+            .unwrap();
+        add_expense(&store, list_id, first, second, 500).await;
+
+        assert!(store.delete_user(second).await.unwrap());
+        let transactions = store.transactions().await.unwrap();
+        assert_eq!(
+            transactions[0].amounts.keys().collect::<Vec<_>>(),
+            vec![&first]
+        );
+
+        // Amounts of unknown users are not stored.
+        add_expense(&store, list_id, first, second, 500).await;
+        let transactions = store.transactions().await.unwrap();
+        assert_eq!(
+            transactions[1].amounts.keys().collect::<Vec<_>>(),
+            vec![&first]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_delete_list_keeps_expenses() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let list = List::new(0, "lunch".parse().unwrap(), date);
+        let list_id = store.create_list(&list).await.unwrap();
+        add_expense(&store, list_id, first, second, 500).await;
+
+        assert!(store.delete_list(list_id).await.unwrap());
+        let transactions = store.transactions().await.unwrap();
+        assert_eq!(transactions[0].list_id, None);
+        assert_eq!(transactions[0].amounts.len(), 2);
+
+        let list_id = store.create_list(&list).await.unwrap();
+        assert!(store
+            .transactions()
+            .await
+            .unwrap()
+            .iter()
+            .all(|transaction| transaction.list_id != Some(list_id)));
+    }
+
+    #[actix_rt::test]
+    async fn test_ids_are_not_reused() {
+        // Start from the schema before ids were autoincremented.
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..15] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute_batch(
+            "PRAGMA user_version = 15;
+             INSERT INTO users (id, username, password) VALUES (1, 'First User', 'hash');
+             INSERT INTO users (id, username, password) VALUES (2, 'Second User', 'hash');
+             INSERT INTO lists (id, type, date) VALUES (1, 'lunch', '2020-12-24');
+             INSERT INTO attendees (list_id, user_id) VALUES (1, 2);
+             INSERT INTO transactions (id, list_id, description, created)
+                 VALUES (1, 1, 'Soup', '2020-12-24T12:00:00Z');
+             INSERT INTO transaction_amounts (transaction_id, user_id, amount)
+                 VALUES (1, 2, 500), (1, 3, -500);",
+        )
+        .unwrap();
+        let store = SqliteStore::from_connection(conn).unwrap();
+        assert!(store.attendees(1).await.unwrap().contains("Second User"));
+        // Amounts of users that were deleted before the migration are dropped.
+        let transactions = store.transactions().await.unwrap();
+        assert_eq!(transactions[0].amounts.keys().collect::<Vec<_>>(), vec![&2]);
+
+        assert!(store.delete_user(2).await.unwrap());
+        assert_eq!(store.create_user("Third User", "hash").await.unwrap(), 3);
+        assert!(store.delete_list(1).await.unwrap());
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let list = List::new(0, "lunch".parse().unwrap(), date);
+        assert_eq!(store.create_list(&list).await.unwrap(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_migrate_and_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let user_id = store.create_user("Sir User", "hash").await.unwrap();
+        assert!(store.create_user("Sir User", "hash").await.is_err());
+        assert_eq!(store.user_id("Sir User").await.unwrap(), Some(user_id));
+
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let list = List::new(0, "lunch".parse().unwrap(), date);
+        let list_id = store.create_list(&list).await.unwrap();
+        assert!(matches!(
+            store.create_list(&list).await,
+            Err(ServiceError::ListAlreadyExists { id }) if id == list_id
+        ));
+        assert_eq!(
+            store
+                .add_attendee(list_id, user_id, &Guests::default(), None)
+                .await
+                .unwrap(),
+            Attendance::Joined
+        );
+        assert_eq!(
+            store
+                .add_attendee(list_id, user_id, &Guests::default(), None)
+                .await
+                .unwrap(),
+            Attendance::Unchanged
+        );
+        assert_eq!(store.attendee_count(list_id).await.unwrap(), 1);
+        assert!(store.attendees(list_id).await.unwrap().contains("Sir User"));
+        assert_eq!(store.list_ids_on(date).await.unwrap(), vec![list_id]);
+
+        let mut dinner = List::new(0, "dinner".parse().unwrap(), date);
+        dinner.id = store.create_list(&dinner).await.unwrap();
+        dinner.list_type = "lunch".parse().unwrap();
+        assert!(matches!(
+            store.update_list(&dinner).await,
+            Err(ServiceError::ListAlreadyExists { id }) if id == list_id
+        ));
+        dinner.date = date.succ_opt().unwrap();
+        dinner.title = Some("Boxing Day".to_string());
+        assert!(store.update_list(&dinner).await.unwrap());
+        let updated = store.list(dinner.id).await.unwrap().unwrap();
+        assert_eq!(updated.date, dinner.date);
+        assert_eq!(updated.title, dinner.title);
+        assert_eq!(
+            store
+                .list_ids_between(Some(date), None, None)
+                .await
+                .unwrap(),
+            vec![list_id, dinner.id]
+        );
+        assert_eq!(
+            store
+                .list_ids_between(Some(dinner.date), None, Some(&dinner.list_type))
+                .await
+                .unwrap(),
+            vec![dinner.id]
+        );
+
+        assert!(store.delete_list(list_id).await.unwrap());
+        assert_eq!(store.attendee_count(list_id).await.unwrap(), 0);
+
+        let other_id = store.create_user("Other User", "hash").await.unwrap();
+        let settlement = Transaction::settlement(user_id, other_id, 250);
+        let id = store.add_transaction(&settlement).await.unwrap();
+        assert_eq!(
+            store.transactions().await.unwrap(),
+            vec![Transaction { id, ..settlement }]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_waitlist_promotion() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let mut list = List::new(0, "lunch".parse().unwrap(), date);
+        list.capacity = Some(1);
+        let list_id = store.create_list(&list).await.unwrap();
+
+        let attendance = store
+            .add_attendee(list_id, first, &Guests::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Joined);
+        let attendance = store
+            .add_attendee(list_id, second, &Guests::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Waitlisted);
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![second]);
+
+        assert!(store.remove_attendee(list_id, first).await.unwrap());
+        assert!(store.attendee_ids(list_id).await.unwrap().contains(&second));
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_delete_user_promotes_waitlist() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let mut list = List::new(0, "lunch".parse().unwrap(), date);
+        list.capacity = Some(1);
+        let list_id = store.create_list(&list).await.unwrap();
+        for user_id in &[first, second] {
+            store
+                .add_attendee(list_id, *user_id, &Guests::default(), None)
+                .await
+                .unwrap();
+        }
+
+        assert!(store.delete_user(first).await.unwrap());
+        assert!(store.attendee_ids(list_id).await.unwrap().contains(&second));
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_capacity_counts_guests() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let first = store.create_user("First User", "hash").await.unwrap();
+        let second = store.create_user("Second User", "hash").await.unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+        let mut list = List::new(0, "lunch".parse().unwrap(), date);
+        list.capacity = Some(3);
+        let list_id = store.create_list(&list).await.unwrap();
+
+        let one_guest = Guests {
+            count: 1,
+            names: Vec::new(),
+        };
+        let two_guests = Guests {
+            count: 2,
+            names: Vec::new(),
+        };
+        let attendance = store
+            .add_attendee(list_id, first, &one_guest, None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Joined);
+        let attendance = store
+            .add_attendee(list_id, second, &two_guests, None)
+            .await
+            .unwrap();
+        assert_eq!(attendance, Attendance::Waitlisted);
+
+        assert!(store.remove_attendee(list_id, first).await.unwrap());
+        assert!(store.attendee_ids(list_id).await.unwrap().contains(&second));
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+
+        // An attendee cannot bring more guests than there are seats left.
+        let three_guests = Guests {
+            count: 3,
+            names: Vec::new(),
+        };
+        let result = store
+            .add_attendee(list_id, second, &three_guests, None)
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotEnoughSeats)));
+        assert_eq!(store.guests(list_id).await.unwrap()[&second], two_guests);
+
+        // Bringing fewer guests frees seats for the waitlist.
+        store
+            .add_attendee(list_id, first, &Guests::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(store.waitlist_ids(list_id).await.unwrap(), vec![first]);
+        store
+            .add_attendee(list_id, second, &one_guest, None)
+            .await
+            .unwrap();
+        assert!(store.waitlist_ids(list_id).await.unwrap().is_empty());
+    }
+}
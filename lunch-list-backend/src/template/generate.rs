@@ -0,0 +1,131 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Duration as DateDuration, Local, NaiveDate};
+
+use crate::{errors::ServiceError, store::Store};
+
+/// Time between two runs of the list generator.
+const GENERATE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Creates the lists of all templates from `start` up to `days` days ahead,
+/// skipping holidays and dates that already have a list of the same type.
+/// Dates up to the `generated_until` date of a template are skipped as well,
+/// so that deleted lists are not created again. Returns the ids of the
+/// created lists.
+pub async fn create_upcoming_lists(
+    store: &dyn Store,
+    start: NaiveDate,
+    days: u32,
+) -> Result<Vec<usize>, ServiceError> {
+    let end = start + DateDuration::days(i64::from(days));
+    let mut ids = Vec::new();
+    for template in store.templates().await? {
+        let first = match template.generated_until {
+            Some(date) if date >= end => continue,
+            Some(date) if date >= start => date + DateDuration::days(1),
+            _ => start,
+        };
+        let mut date = first;
+        while date <= end {
+            if template.applies_to(date) && store.holiday(date).await?.is_none() {
+                match store.create_list(&template.to_list(date)).await {
+                    Ok(id) => ids.push(id),
+                    Err(ServiceError::ListAlreadyExists { .. }) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            date += DateDuration::days(1);
+        }
+        store.set_generated_until(template.id, end).await?;
+    }
+    Ok(ids)
+}
+
+/// Runs forever, creating the lists of all templates `days` days ahead once
+/// every hour.
+pub async fn generate_lists(store: Arc<dyn Store>, days: u32) {
+    loop {
+        let today = Local::now().naive_local().date();
+        match create_upcoming_lists(store.as_ref(), today, days).await {
+            Ok(ids) if !ids.is_empty() => {
+                log::info!("Created {} list(s) from templates", ids.len())
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to create lists from templates: {:?}", e),
+        }
+        actix_rt::time::delay_for(GENERATE_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::*;
+    use crate::{holiday::Holiday, store::MemoryStore, template::Template};
+
+    #[actix_rt::test]
+    async fn test_create_upcoming_lists() {
+        let store = MemoryStore::new();
+        store
+            .create_template(&Template {
+                id: 0,
+                list_type: "lunch".parse().unwrap(),
+                weekdays: vec![Weekday::Mon, Weekday::Wed],
+                deadline: None,
+                capacity: Some(24),
+                generated_until: None,
+            })
+            .await
+            .unwrap();
+        store
+            .add_holiday(&Holiday {
+                date: NaiveDate::from_ymd_opt(2020, 12, 23).unwrap(),
+                name: "Closed".to_string(),
+            })
+            .await
+            .unwrap();
+        // Monday
+        let start = NaiveDate::from_ymd_opt(2020, 12, 21).unwrap();
+
+        let ids = create_upcoming_lists(&store, start, 9).await.unwrap();
+        assert_eq!(ids.len(), 3);
+        let list = store.list(ids[1]).await.unwrap().unwrap();
+        assert_eq!(list.date, NaiveDate::from_ymd_opt(2020, 12, 28).unwrap());
+        assert_eq!(list.capacity, Some(24));
+
+        let ids = create_upcoming_lists(&store, start, 9).await.unwrap();
+        assert!(ids.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_deleted_lists_are_not_created_again() {
+        let store = MemoryStore::new();
+        store
+            .create_template(&Template {
+                id: 0,
+                list_type: "lunch".parse().unwrap(),
+                weekdays: vec![Weekday::Mon, Weekday::Tue],
+                deadline: None,
+                capacity: None,
+                generated_until: None,
+            })
+            .await
+            .unwrap();
+        // Monday
+        let start = NaiveDate::from_ymd_opt(2020, 12, 21).unwrap();
+
+        let ids = create_upcoming_lists(&store, start, 6).await.unwrap();
+        assert_eq!(ids.len(), 2);
+        assert!(store.delete_list(ids[0]).await.unwrap());
+
+        // The next day the deleted list is not created again, but the lists
+        // of the days that came into range are.
+        let next_day = start + DateDuration::days(1);
+        let ids = create_upcoming_lists(&store, next_day, 6).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        let list = store.list(ids[0]).await.unwrap().unwrap();
+        assert_eq!(list.date, NaiveDate::from_ymd_opt(2020, 12, 28).unwrap());
+        assert!(store.list_ids_on(start).await.unwrap().is_empty());
+    }
+}
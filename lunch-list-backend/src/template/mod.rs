@@ -0,0 +1,17 @@
+use actix_web::web;
+
+mod generate;
+mod routes;
+mod template_model;
+
+pub use generate::{create_upcoming_lists, generate_lists};
+pub use template_model::Template;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/template")
+            .service(routes::get_templates)
+            .service(routes::put_template)
+            .service(routes::delete_template),
+    );
+}
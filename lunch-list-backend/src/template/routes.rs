@@ -0,0 +1,50 @@
+use actix_web::{delete, get, put, web, HttpResponse, Responder};
+use serde_json::json;
+use validator::Validate;
+
+use super::template_model::Template;
+use crate::{auth::Claims, errors::ServiceError, store::Store, AppState};
+
+#[get("")]
+async fn get_templates(
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let templates = store.templates().await?;
+    Ok(HttpResponse::Ok().json(json!({ "templates": templates })))
+}
+
+#[put("")]
+async fn put_template(
+    template: web::Json<Template>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, ServiceError> {
+    template.validate()?;
+    if !state.list_types.contains(&template.list_type) {
+        return Err(ServiceError::UnknownListType {
+            list_type: template.list_type.to_string(),
+        });
+    }
+
+    let template = Template {
+        generated_until: None,
+        ..template.into_inner()
+    };
+    let id = store.create_template(&template).await?;
+    Ok(HttpResponse::Created().json(json!({ "id": id })))
+}
+
+#[delete("/{id}")]
+async fn delete_template(
+    id: web::Path<usize>,
+    _claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    if store.delete_template(id.into_inner()).await? {
+        Ok(HttpResponse::NoContent())
+    } else {
+        Ok(HttpResponse::NotFound())
+    }
+}
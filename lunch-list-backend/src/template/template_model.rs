@@ -0,0 +1,47 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use validator_derive::Validate;
+
+use crate::list::{List, ListType};
+
+/// Recurring list that is created automatically on the given weekdays.
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+pub struct Template {
+    #[serde(skip_deserializing)]
+    pub id: usize,
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    #[validate(length(min = 1, message = "At least one weekday is required"))]
+    pub weekdays: Vec<Weekday>,
+    /// Local time of day at which the sign-up of created lists closes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<NaiveTime>,
+    /// Capacity of created lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, message = "Capacity must be at least 1"))]
+    pub capacity: Option<usize>,
+    /// Last date for which lists were created, lists that were deleted since
+    /// are not created again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_until: Option<NaiveDate>,
+}
+
+impl Template {
+    /// Returns true when the template creates a list on the given date.
+    pub fn applies_to(&self, date: NaiveDate) -> bool {
+        self.weekdays.contains(&date.weekday())
+    }
+
+    /// Returns the list this template creates on the given date.
+    pub fn to_list(&self, date: NaiveDate) -> List {
+        let mut list = List::new(0, self.list_type.clone(), date);
+        list.deadline = self.deadline.and_then(|time| {
+            chrono::Local
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|deadline| deadline.with_timezone(&Utc))
+        });
+        list.capacity = self.capacity;
+        list
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use validator_derive::Validate;
+
+/// Dietary preferences of a user.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate)]
+pub struct Diet {
+    #[serde(default)]
+    pub vegetarian: bool,
+    #[serde(default)]
+    pub vegan: bool,
+    #[serde(default)]
+    pub gluten_free: bool,
+    /// Free text, e.g. "nut".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 100, message = "Allergies cannot be longer than 100 characters"))]
+    pub allergies: Option<String>,
+}
+
+/// Dietary preferences of all attendees of a list.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DietSummary {
+    pub attendees: usize,
+    pub vegetarian: usize,
+    pub vegan: usize,
+    pub gluten_free: usize,
+    /// Number of attendees per allergy.
+    pub allergies: BTreeMap<String, usize>,
+    /// Human readable summary, e.g. "12 attendees: 3 vegetarian, 1 nut allergy".
+    pub summary: String,
+}
+
+impl Diet {
+    /// Returns true when the user has no dietary preferences.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl DietSummary {
+    pub fn new<'a>(diets: impl IntoIterator<Item = &'a Diet>) -> Self {
+        let mut summary = Self::default();
+        for diet in diets {
+            summary.attendees += 1;
+            summary.vegetarian += diet.vegetarian as usize;
+            summary.vegan += diet.vegan as usize;
+            summary.gluten_free += diet.gluten_free as usize;
+            let allergies = diet.allergies.as_deref().map(str::trim).unwrap_or_default();
+            if !allergies.is_empty() {
+                *summary
+                    .allergies
+                    .entry(allergies.to_lowercase())
+                    .or_default() += 1;
+            }
+        }
+        summary.summary = summary.to_string();
+        summary
+    }
+}
+
+impl fmt::Display for DietSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let noun = if self.attendees == 1 {
+            "attendee"
+        } else {
+            "attendees"
+        };
+        write!(f, "{} {}", self.attendees, noun)?;
+
+        let mut parts = Vec::new();
+        for (count, name) in &[
+            (self.vegetarian, "vegetarian"),
+            (self.vegan, "vegan"),
+            (self.gluten_free, "gluten-free"),
+        ] {
+            if *count > 0 {
+                parts.push(format!("{} {}", count, name));
+            }
+        }
+        for (allergy, count) in &self.allergies {
+            parts.push(format!("{} {} allergy", count, allergy));
+        }
+        if !parts.is_empty() {
+            write!(f, ": {}", parts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let diets = vec![
+            Diet {
+                vegetarian: true,
+                ..Diet::default()
+            },
+            Diet {
+                allergies: Some(" Nut ".to_string()),
+                ..Diet::default()
+            },
+            Diet::default(),
+        ];
+        assert_eq!(
+            DietSummary::new(&diets).summary,
+            "3 attendees: 1 vegetarian, 1 nut allergy"
+        );
+    }
+}
@@ -1,7 +1,16 @@
 use actix_web::web;
 
+mod diet;
 mod routes;
+mod user_model;
+
+pub use diet::{Diet, DietSummary};
+pub use user_model::User;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/user").service(routes::get_user));
+    cfg.service(
+        web::scope("/user")
+            .service(routes::get_user)
+            .service(routes::put_diet),
+    );
 }
@@ -1,9 +1,38 @@
-use actix_web::{get, HttpResponse, Responder};
+use actix_web::{get, put, web, HttpResponse, Responder};
 use serde_json::json;
+use validator::Validate;
 
-use crate::auth::Claims;
+use super::diet::Diet;
+use crate::{auth::Claims, errors::ServiceError, store::Store};
 
 #[get("")]
-pub async fn get_user(claims: Claims) -> impl Responder {
-    HttpResponse::Ok().json(json!({ "id": claims.sub, "username": claims.name }))
+pub async fn get_user(
+    claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let user = store
+        .user(claims.sub)
+        .await?
+        .ok_or(ServiceError::Unauthorized)?;
+    Ok(HttpResponse::Ok().json(json!({
+        "id": user.id,
+        "username": user.username,
+        "diet": user.diet,
+    })))
+}
+
+#[put("/diet")]
+pub async fn put_diet(
+    diet: web::Json<Diet>,
+    claims: Claims,
+    store: web::Data<dyn Store>,
+) -> Result<impl Responder, ServiceError> {
+    let diet = diet.into_inner();
+    diet.validate()?;
+
+    if store.set_diet(claims.sub, &diet).await? {
+        Ok(HttpResponse::NoContent())
+    } else {
+        Err(ServiceError::Unauthorized)
+    }
 }
@@ -0,0 +1,9 @@
+use super::diet::Diet;
+
+#[derive(Clone)]
+pub struct User {
+    pub id: usize,
+    pub username: String,
+    pub password: String,
+    pub diet: Diet,
+}
@@ -0,0 +1,1270 @@
+use std::sync::Arc;
+
+use actix_web::{cookie::Cookie, http::StatusCode, test, web, App, FromRequest};
+use serde_json::{json, Value};
+
+use chrono::{Duration, Local, NaiveDate};
+use lunch_list_backend::{
+    auth, config,
+    holiday::Holiday,
+    list::{List, ListTypes},
+    store::{MemoryStore, Store},
+    AppState,
+};
+
+const TOKEN_SECRET: &str = "token-secret";
+const SIGNUP_SECRET: &str = "signup-secret";
+
+macro_rules! test_app {
+    () => {{
+        let store: Arc<dyn Store> = Arc::new(MemoryStore::new());
+        test_app!(store)
+    }};
+    ($store:expr) => {{
+        let store: Arc<dyn Store> = $store;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::from(store))
+                .data(AppState {
+                    token_secret: TOKEN_SECRET.to_string(),
+                    signup_secret: SIGNUP_SECRET.to_string(),
+                    list_types: ListTypes::default(),
+                })
+                .app_data(auth::Claims::configure(|cfg| cfg.secret(TOKEN_SECRET)))
+                .configure(config),
+        )
+        .await
+    }};
+}
+
+/// Signs up and logs in a user, returns the access token and refresh cookie.
+macro_rules! login {
+    ($app:expr, $username:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(&json!({
+                "username": $username,
+                "password": "hunter2",
+                "secret": SIGNUP_SECRET,
+            }))
+            .to_request();
+        let res = test::call_service(&mut $app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&json!({ "username": $username, "password": "hunter2" }))
+            .to_request();
+        let res = test::call_service(&mut $app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let cookie = refresh_cookie(&res);
+        let body: Value = test::read_body_json(res).await;
+        (body["token"].as_str().unwrap().to_string(), cookie)
+    }};
+}
+
+fn refresh_cookie<B>(res: &actix_web::dev::ServiceResponse<B>) -> Cookie<'static> {
+    res.response()
+        .cookies()
+        .find(|c| c.name() == "refresh_token")
+        .expect("missing refresh token cookie")
+        .into_owned()
+}
+
+#[actix_rt::test]
+async fn test_login_with_wrong_password() {
+    let mut app = test_app!();
+    login!(app, "Sir User");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&json!({ "username": "Sir User", "password": "hunter3" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn test_refresh_after_logout() {
+    let mut app = test_app!();
+    let (_, cookie) = login!(app, "Sir User");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .cookie(cookie.clone())
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/auth/refresh")
+        .cookie(cookie)
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn test_put_list_add_and_remove_user() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let auth = ("Authorization", format!("Bearer {}", token));
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header(auth.0, auth.1.clone())
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let body: Value = test::read_body_json(res).await;
+    let id = body["id"].as_u64().unwrap();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header(auth.0, auth.1.clone())
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header(auth.0, auth.1.clone())
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["type"], "lunch");
+    assert_eq!(list["users"], json!(["Sir User"]));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/list/{}/user", id))
+        .header(auth.0, auth.1.clone())
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header(auth.0, auth.1.clone())
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["users"], json!([]));
+
+    let req = test::TestRequest::get()
+        .uri("/api/list")
+        .header(auth.0, auth.1)
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(lists["lists"][0]["counts"]["yes"], 0);
+}
+
+#[actix_rt::test]
+async fn test_put_list_with_unknown_type() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "brunch", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_put_duplicate_list() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let mut ids = Vec::new();
+    for list_type in &["lunch", "dinner", "lunch"] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": list_type, "date": "2020-12-24" }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        ids.push((
+            res.status(),
+            test::read_body_json::<Value, _>(res).await["id"].clone(),
+        ));
+    }
+
+    assert_eq!(ids[0].0, StatusCode::CREATED);
+    assert_eq!(ids[1].0, StatusCode::CREATED);
+    assert_ne!(ids[0].1, ids[1].1);
+    assert_eq!(ids[2].0, StatusCode::CONFLICT);
+    assert_eq!(ids[2].1, ids[0].1);
+}
+
+#[actix_rt::test]
+async fn test_add_user_after_deadline() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({
+            "type": "lunch",
+            "date": "2020-12-24",
+            "deadline": "2020-12-24T15:00:00Z"
+        }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    for req in [test::TestRequest::put(), test::TestRequest::delete()] {
+        let req = req
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[actix_rt::test]
+async fn test_waitlist_promotion() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 1 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let mut statuses = Vec::new();
+    for token in &[&token, &other_token] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .to_request();
+        statuses.push(test::call_service(&mut app, req).await.status());
+    }
+    assert_eq!(statuses, vec![StatusCode::CREATED, StatusCode::ACCEPTED]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["users"], json!(["Sir User"]));
+    assert_eq!(list["waitlist"], json!(["Other User"]));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["users"], json!(["Other User"]));
+    assert_eq!(list["waitlist"], json!([]));
+}
+
+#[actix_rt::test]
+async fn test_capacity_counts_guests() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 3 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    // Two seats are left, not enough for a user with two guests.
+    let mut statuses = Vec::new();
+    for (token, guests) in &[(&token, 0), (&other_token, 2)] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "guests": guests }))
+            .to_request();
+        statuses.push(test::call_service(&mut app, req).await.status());
+    }
+    assert_eq!(statuses, vec![StatusCode::CREATED, StatusCode::ACCEPTED]);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["users"], json!(["Other User"]));
+    assert_eq!(list["waitlist"], json!([]));
+}
+
+#[actix_rt::test]
+async fn test_guests_cannot_exceed_capacity() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 2 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let mut statuses = Vec::new();
+    for guests in &[0, 10, 1] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "guests": guests }))
+            .to_request();
+        statuses.push(test::call_service(&mut app, req).await.status());
+    }
+    assert_eq!(
+        statuses,
+        vec![
+            StatusCode::CREATED,
+            StatusCode::CONFLICT,
+            StatusCode::NO_CONTENT
+        ]
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["guests"]["Sir User"]["count"], json!(1));
+}
+
+#[actix_rt::test]
+async fn test_fewer_guests_promote_waitlist() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 3 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    // The other user only fits once both parties have shrunk.
+    let mut statuses = Vec::new();
+    for (token, guests) in &[
+        (&token, 2),
+        (&other_token, 1),
+        (&token, 1),
+        (&other_token, 0),
+    ] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "guests": guests }))
+            .to_request();
+        statuses.push(test::call_service(&mut app, req).await.status());
+    }
+    assert_eq!(
+        statuses,
+        vec![
+            StatusCode::CREATED,
+            StatusCode::ACCEPTED,
+            StatusCode::NO_CONTENT,
+            StatusCode::CREATED
+        ]
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["waitlist"], json!([]));
+    assert_eq!(list["users"].as_array().map(Vec::len), Some(2));
+}
+
+#[actix_rt::test]
+async fn test_patch_capacity_and_deadline() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 0 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24", "capacity": 1 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+    for token in &[&token, &other_token] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .to_request();
+        test::call_service(&mut app, req).await;
+    }
+
+    // Raising the capacity promotes the waitlist.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "capacity": 2, "deadline": "2020-12-24T10:00:00Z" }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["capacity"], json!(2));
+    assert_eq!(list["deadline"], json!("2020-12-24T10:00:00Z"));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["waitlist"], json!([]));
+    assert_eq!(list["users"].as_array().unwrap().len(), 2);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "capacity": 0 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "capacity": null, "deadline": null }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list.get("capacity"), None);
+    assert_eq!(list.get("deadline"), None);
+}
+
+#[actix_rt::test]
+async fn test_add_user_with_guests_and_note() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "guest_names": ["x".repeat(51)] }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({
+            "guests": 2,
+            "guest_names": ["Lady Guest"],
+            "note": "arriving 12:30"
+        }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        list["guests"],
+        json!({ "Sir User": { "count": 2, "names": ["Lady Guest"] } })
+    );
+    assert_eq!(list["notes"], json!({ "Sir User": "arriving 12:30" }));
+
+    let req = test::TestRequest::get()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(lists["lists"][0]["counts"]["yes"], json!(3));
+}
+
+#[actix_rt::test]
+async fn test_rsvp_counts_and_unanswered() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+    login!(app, "Silent User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "rsvp": "yes" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", other_token))
+        .set_json(&json!({ "rsvp": "maybe" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}/unanswered", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let unanswered: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(unanswered["users"], json!(["Silent User"]));
+
+    // Declining removes the attendance.
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "rsvp": "no" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["users"], json!([]));
+    assert_eq!(list["maybe"], json!(["Other User"]));
+    assert_eq!(list["no"], json!(["Sir User"]));
+
+    let req = test::TestRequest::get()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        lists["lists"][0]["counts"],
+        json!({ "yes": 0, "maybe": 1, "no": 1 })
+    );
+}
+
+#[actix_rt::test]
+async fn test_diet_summary() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/user/diet")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "vegetarian": true, "allergies": "nut" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/user")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let user: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(user["diet"]["vegetarian"], json!(true));
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        list["diet"]["summary"],
+        json!("1 attendee: 1 vegetarian, 1 nut allergy")
+    );
+}
+
+#[actix_rt::test]
+async fn test_put_and_delete_template() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/template")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "weekdays": [] }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri("/api/template")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({
+            "type": "lunch",
+            "weekdays": ["Mon", "Tue", "Wed", "Thu", "Fri"],
+            "deadline": "11:00:00",
+        }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::get()
+        .uri("/api/template")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let templates: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(templates["templates"][0]["weekdays"][4], json!("Fri"));
+    assert_eq!(templates["templates"][0]["deadline"], json!("11:00:00"));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/template/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+}
+
+#[actix_rt::test]
+async fn test_put_list_on_holiday() {
+    let store: Arc<dyn Store> = Arc::new(MemoryStore::new());
+    store
+        .add_holiday(&Holiday {
+            date: NaiveDate::from_ymd_opt(2020, 12, 25).unwrap(),
+            name: "Christmas".to_string(),
+        })
+        .await
+        .unwrap();
+    let mut app = test_app!(store.clone());
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-25" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri("/api/holiday")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let holidays: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        holidays["holidays"],
+        json!([{ "date": "2020-12-25", "name": "Christmas" }])
+    );
+}
+
+#[actix_rt::test]
+async fn test_patch_list_details() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "dinner", "date": "2020-12-24", "cook": "Sir User" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "title": "Christmas Eve", "menu": "* Soup\n* Turkey", "cook": null }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["title"], json!("Christmas Eve"));
+    assert_eq!(list["menu"], json!("* Soup\n* Turkey"));
+    assert_eq!(list.get("cook"), None);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "title": "x".repeat(101) }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["title"], json!("Christmas Eve"));
+}
+
+#[actix_rt::test]
+async fn test_patch_list_of_removed_type() {
+    let store: Arc<dyn Store> = Arc::new(MemoryStore::new());
+    let date = NaiveDate::from_ymd_opt(2020, 12, 24).unwrap();
+    let id = store
+        .create_list(&List::new(0, "brunch".parse().unwrap(), date))
+        .await
+        .unwrap();
+    let mut app = test_app!(store.clone());
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "title": "Christmas Brunch" }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["title"], json!("Christmas Brunch"));
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "breakfast" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_patch_list_date_and_type() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let mut ids = Vec::new();
+    for list_type in &["lunch", "dinner"] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": list_type, "date": "2020-12-24" }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        ids.push(test::read_body_json::<Value, _>(res).await["id"].clone());
+    }
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", ids[1]))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(res).await;
+    assert_eq!(body["id"], ids[0]);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", ids[1]))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "lunch", "date": "2020-12-28" }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["type"], json!("lunch"));
+    assert_eq!(list["date"], json!("2020-12-28"));
+
+    // Lists are ordered by their new date, newest first.
+    let req = test::TestRequest::get()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(lists["lists"][0]["id"], ids[1]);
+}
+
+#[actix_rt::test]
+async fn test_list_filters() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let mut ids = Vec::new();
+    for (list_type, date) in &[
+        ("lunch", "2020-12-21"),
+        ("dinner", "2020-12-21"),
+        ("lunch", "2020-12-23"),
+        ("lunch", "2020-12-28"),
+    ] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": list_type, "date": date }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        ids.push(test::read_body_json::<Value, _>(res).await["id"].clone());
+    }
+
+    let list_ids = |lists: &Value| -> Vec<Value> {
+        lists["lists"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|list| list["id"].clone())
+            .collect()
+    };
+
+    let req = test::TestRequest::get()
+        .uri("/api/list?start_date=2020-12-21&end_date=2020-12-27&type=lunch")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list_ids(&lists), vec![ids[2].clone(), ids[0].clone()]);
+
+    let req = test::TestRequest::get()
+        .uri("/api/list?start_date=2020-12-21&end_date=2020-12-21&rev=true")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list_ids(&lists), vec![ids[0].clone(), ids[1].clone()]);
+
+    // A missing bound is unlimited and paging applies to the filtered lists.
+    let req = test::TestRequest::get()
+        .uri("/api/list?start_date=2020-12-22&from=1&len=1")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let lists: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list_ids(&lists), vec![ids[2].clone()]);
+
+    let req = test::TestRequest::get()
+        .uri("/api/list?start_date=2020-12-28&end_date=2020-12-21")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_expense_balances_and_settlement() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "dinner", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/list/{}/expense", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "amount": 3000, "description": "Groceries" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    for (token, guests) in &[(&token, 0), (&other_token, 1)] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "guests": guests }))
+            .to_request();
+        test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/list/{}/expense", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "amount": 3000, "description": "   " }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/list/{}/expense", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "amount": 3000, "description": "Groceries" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri("/api/ledger/balance")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let balances: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        balances["balances"],
+        json!([
+            { "username": "Other User", "balance": -2000 },
+            { "username": "Sir User", "balance": 2000 },
+        ])
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/ledger/settlement")
+        .header("Authorization", format!("Bearer {}", other_token))
+        .set_json(&json!({ "to": "Sir User", "amount": 2000 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri("/api/ledger/balance")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let balances: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(balances["balances"][0]["balance"], json!(0));
+    assert_eq!(balances["balances"][1]["balance"], json!(0));
+}
+
+#[actix_rt::test]
+async fn test_cook_rotation() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    let mut ids = Vec::new();
+    for (date, cook) in &[
+        ("2020-12-23", json!("Sir User")),
+        ("2020-12-24", json!(null)),
+    ] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": "dinner", "date": date, "cook": cook }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+        for token in &[&token, &other_token] {
+            let req = test::TestRequest::put()
+                .uri(&format!("/api/list/{}/user", id))
+                .header("Authorization", format!("Bearer {}", token))
+                .to_request();
+            test::call_service(&mut app, req).await;
+        }
+        ids.push(id);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}/cook", ids[1]))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let rotation: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(rotation["suggestion"], json!("Other User"));
+    assert_eq!(rotation["candidates"][1]["cooked"], json!(1));
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/cook", ids[1]))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["cook"], json!("Other User"));
+}
+
+#[actix_rt::test]
+async fn test_recipe_shopping_list() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let req = test::TestRequest::put()
+        .uri("/api/recipe")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({
+            "name": "Risotto",
+            "ingredients": [
+                { "name": "rice", "quantity": 80, "unit": "g" },
+                { "name": "onion", "quantity": 0.25 }
+            ]
+        }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let recipe_id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::put()
+        .uri("/api/list")
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "type": "dinner", "date": "2020-12-24" }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}/shopping", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "recipe": 99 }))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "recipe": recipe_id }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["recipe"], recipe_id);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "guests": 3 }))
+        .to_request();
+    test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/list/{}/shopping", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let shopping: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(shopping["headcount"], json!(4));
+    assert_eq!(
+        shopping["items"],
+        json!([
+            { "name": "rice", "quantity": 320.0, "unit": "g" },
+            { "name": "onion", "quantity": 1.0, "unit": "" }
+        ])
+    );
+
+    // Deleting the recipe removes it from the list, which can still be edited.
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/recipe/{}", recipe_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/list/{}", id))
+        .header("Authorization", format!("Bearer {}", token))
+        .set_json(&json!({ "title": "Risotto night" }))
+        .to_request();
+    let list: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(list["title"], json!("Risotto night"));
+    assert_eq!(list.get("recipe"), None);
+}
+
+#[actix_rt::test]
+async fn test_calendar_feed() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    let today = Local::now().naive_local().date();
+    let mut ids = Vec::new();
+    for (list_type, days) in &[("lunch", 1), ("dinner", 2), ("lunch", -1)] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": list_type, "date": today + Duration::days(*days) }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        ids.push(test::read_body_json::<Value, _>(res).await["id"].clone());
+    }
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/list/{}/user", ids[1]))
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    test::call_service(&mut app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/calendar")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let body: Value = test::read_response_json(&mut app, req).await;
+    let url = body["url"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get().uri(&url).to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let ics = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("SUMMARY:Lunch\r\n"));
+    assert!(ics.contains("SUMMARY:✔ Dinner\r\n"));
+    assert!(!ics.contains(&format!("UID:list-{}@", ids[2])));
+
+    let req = test::TestRequest::get()
+        .uri(&url.replace("/1/", "/2/"))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // The URL stays the same until it is reset, which revokes the old one.
+    let req = test::TestRequest::get()
+        .uri("/api/calendar")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let body: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(body["url"], json!(url));
+    let req = test::TestRequest::post()
+        .uri("/api/calendar/reset")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let body: Value = test::read_response_json(&mut app, req).await;
+    let new_url = body["url"].as_str().unwrap().to_string();
+    assert_ne!(new_url, url);
+
+    let req = test::TestRequest::get().uri(&url).to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let req = test::TestRequest::get().uri(&new_url).to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn test_attendance_report() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+    let (other_token, _) = login!(app, "Other User");
+
+    for (list_type, date) in &[
+        ("lunch", "2020-12-01"),
+        ("dinner", "2020-12-01"),
+        ("lunch", "2020-12-02"),
+        ("lunch", "2021-01-04"),
+    ] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": list_type, "date": date }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+        // Only the lunches are joined, the dinner is filtered out by type.
+        if *list_type == "dinner" {
+            continue;
+        }
+        for (token, guests) in &[(&token, 1), (&other_token, 0)] {
+            let req = test::TestRequest::put()
+                .uri(&format!("/api/list/{}/user", id))
+                .header("Authorization", format!("Bearer {}", token))
+                .set_json(&json!({ "guests": guests }))
+                .to_request();
+            test::call_service(&mut app, req).await;
+        }
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/report?start_date=2020-12-01&end_date=2020-12-31&type=lunch")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let csv = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    assert_eq!(
+        csv,
+        "date,type,attendees,guests,total\n\
+         2020-12-01,lunch,2,1,3\n\
+         2020-12-02,lunch,2,1,3\n\
+         \n\
+         username,lists,guests\n\
+         Other User,2,0\n\
+         Sir User,2,2\n"
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/report?start_date=2020-12-01&end_date=2020-12-31&format=xlsx")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get("Content-Disposition").unwrap(),
+        "attachment; filename=\"report-2020-12-01-2020-12-31.xlsx\""
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/report?start_date=2020-12-31&end_date=2020-12-01")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn test_stats() {
+    let mut app = test_app!();
+    let (token, _) = login!(app, "Sir User");
+
+    // Thursday and Friday
+    for (date, guests) in &[("2020-12-24", 2), ("2020-12-25", 0)] {
+        let req = test::TestRequest::put()
+            .uri("/api/list")
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "type": "dinner", "date": date }))
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let id = test::read_body_json::<Value, _>(res).await["id"].clone();
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/list/{}/user", id))
+            .header("Authorization", format!("Bearer {}", token))
+            .set_json(&json!({ "guests": guests }))
+            .to_request();
+        test::call_service(&mut app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/stats?start_date=2020-12-01&end_date=2020-12-31")
+        .header("Authorization", format!("Bearer {}", token))
+        .to_request();
+    let stats: Value = test::read_response_json(&mut app, req).await;
+    assert_eq!(
+        stats["weekdays"],
+        json!([
+            { "weekday": "Thu", "lists": 1, "average": 3.0 },
+            { "weekday": "Fri", "lists": 1, "average": 1.0 }
+        ])
+    );
+    assert_eq!(
+        stats["types"],
+        json!([{ "type": "dinner", "lists": 2, "average": 2.0 }])
+    );
+    assert_eq!(
+        stats["top_attendees"],
+        json!([{ "username": "Sir User", "lists": 2, "guests": 2 }])
+    );
+    assert_eq!(
+        stats["daily"][0],
+        json!({ "date": "2020-12-24", "headcount": 3 })
+    );
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 
+use chrono::NaiveDate;
 use yew::{format::Json, services::fetch};
 
 const BASE_API_URL: &str = "/api";
@@ -14,7 +15,25 @@ pub enum AuthApi {
 
 pub enum ListApi {
     GetAll,
+    Between(NaiveDate, NaiveDate),
     Get(usize),
+    User(usize),
+    Unanswered(usize),
+    Cook(usize),
+    Shopping(usize),
+    Types,
+}
+
+pub enum HolidayApi {
+    GetAll,
+}
+
+pub enum CalendarApi {
+    Url,
+}
+
+pub enum StatsApi {
+    Get,
 }
 
 impl fmt::Display for AuthApi {
@@ -40,7 +59,52 @@ impl fmt::Display for ListApi {
             BASE_API_URL,
             match self {
                 Self::GetAll => "".to_string(),
+                Self::Between(start, end) => format!("?start_date={}&end_date={}", start, end),
                 Self::Get(id) => format!("/{}", id),
+                Self::User(id) => format!("/{}/user", id),
+                Self::Unanswered(id) => format!("/{}/unanswered", id),
+                Self::Cook(id) => format!("/{}/cook", id),
+                Self::Shopping(id) => format!("/{}/shopping", id),
+                Self::Types => "/types".to_string(),
+            }
+        )
+    }
+}
+
+impl fmt::Display for HolidayApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/holiday{}",
+            BASE_API_URL,
+            match self {
+                Self::GetAll => "",
+            }
+        )
+    }
+}
+
+impl fmt::Display for CalendarApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/calendar{}",
+            BASE_API_URL,
+            match self {
+                Self::Url => "",
+            }
+        )
+    }
+}
+
+impl fmt::Display for StatsApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/stats{}",
+            BASE_API_URL,
+            match self {
+                Self::Get => "",
             }
         )
     }
@@ -20,7 +20,7 @@ use yew_router::{
 
 use crate::{
     api::{AuthApi, Response},
-    components::{ListComponent, ListsComponent, LoginComponent},
+    components::{ListComponent, ListsComponent, LoginComponent, StatsComponent},
     models::LoginResponse,
     routes::AppRoute,
     TokenAgent, TokenRequest,
@@ -117,6 +117,7 @@ impl Component for App {
                         AppRoute::Login => html!{<LoginComponent/>},
                         AppRoute::Dashboard => html!{<ListsComponent/>},
                         AppRoute::List { id } => html!{<ListComponent id=id/>},
+                        AppRoute::Stats => html!{<StatsComponent/>},
                         AppRoute::User => html!{"user"},
                         AppRoute::NotFound(Permissive(r)) => html!{format!("Page not found {}", r.unwrap_or_default())},
                         _ => html!{"loading..."},
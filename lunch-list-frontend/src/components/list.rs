@@ -1,24 +1,44 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use log::{error, info};
 use yew::{
     agent::{Bridge, Bridged},
     format::{Json, Nothing},
     html,
     macros::Properties,
-    services::fetch::{FetchService, FetchTask, Request},
+    services::{
+        fetch::{self, FetchService, FetchTask, Request},
+        interval::{IntervalService, IntervalTask},
+    },
     Component, ComponentLink, Html, ShouldRender,
 };
 
 use crate::{
     api::{ListApi, Response},
-    models::ListResponse,
+    models::{
+        AttendanceRequest, CookResponse, Guests, Ingredient, ListResponse, ShoppingResponse,
+        UnansweredResponse,
+    },
     TokenAgent, TokenRequest,
 };
 
 pub struct ListComponent {
     props: Props,
     list: Option<ListResponse>,
+    unanswered: Vec<String>,
+    suggested_cook: Option<String>,
+    shopping: Option<ShoppingResponse>,
     link: ComponentLink<Self>,
     fetch_task: Option<FetchTask>,
+    unanswered_task: Option<FetchTask>,
+    cook_task: Option<FetchTask>,
+    shopping_task: Option<FetchTask>,
+    join_task: Option<FetchTask>,
+    token: String,
+    now: DateTime<Utc>,
+    #[allow(dead_code)]
+    interval_task: IntervalTask,
     #[allow(dead_code)]
     token_agent: Box<dyn Bridge<TokenAgent>>,
 }
@@ -29,7 +49,14 @@ pub struct Props {
 }
 
 pub enum Msg {
-    Fetch(Response<ListResponse>),
+    Fetch(Box<Response<ListResponse>>),
+    FetchUnanswered(Response<UnansweredResponse>),
+    FetchCook(Response<CookResponse>),
+    FetchShopping(Response<ShoppingResponse>),
+    AssignCook(usize),
+    Answer(usize, &'static str),
+    Joined(fetch::Response<Nothing>),
+    Tick,
     UpdateToken(String),
 }
 
@@ -40,11 +67,23 @@ impl Component for ListComponent {
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         let mut token_agent = TokenAgent::bridge(link.callback(Msg::UpdateToken));
         token_agent.send(TokenRequest::GetToken);
+        let interval_task =
+            IntervalService::spawn(Duration::from_secs(1), link.callback(|_| Msg::Tick));
         Self {
             props,
             list: Default::default(),
+            unanswered: Default::default(),
+            suggested_cook: Default::default(),
+            shopping: Default::default(),
             link,
             fetch_task: Default::default(),
+            unanswered_task: Default::default(),
+            cook_task: Default::default(),
+            shopping_task: Default::default(),
+            join_task: Default::default(),
+            token: Default::default(),
+            now: Utc::now(),
+            interval_task,
             token_agent,
         }
     }
@@ -53,11 +92,15 @@ impl Component for ListComponent {
         match msg {
             Msg::Fetch(res) => {
                 self.fetch_task = None;
-                let (meta, Json(data)) = res.into_parts();
+                let (meta, Json(data)) = (*res).into_parts();
                 info!("META: {:?}, {:?}", meta, data);
                 if meta.status.is_success() {
                     match data {
                         Ok(list) => {
+                            match list.recipe {
+                                Some(_) => self.fetch_shopping_list(),
+                                None => self.shopping = None,
+                            }
                             self.list = Some(list);
                             return true;
                         }
@@ -67,13 +110,84 @@ impl Component for ListComponent {
                     error!("Error while fetching lists");
                 }
             }
-            Msg::UpdateToken(token) => {
-                let callback = self.link.callback(Msg::Fetch);
-                let request = Request::get(ListApi::Get(self.props.id).to_string())
-                    .header("Authorization", format!("Bearer {}", &token))
+            Msg::FetchUnanswered(res) => {
+                self.unanswered_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(unanswered) => {
+                            self.unanswered = unanswered.users;
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching unanswered users: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching unanswered users");
+                }
+            }
+            Msg::FetchCook(res) => {
+                self.cook_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(cook) => {
+                            self.suggested_cook = cook.suggestion;
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching cook suggestion: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching cook suggestion");
+                }
+            }
+            Msg::FetchShopping(res) => {
+                self.shopping_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(shopping) => {
+                            self.shopping = Some(shopping);
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching shopping list: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching shopping list");
+                }
+            }
+            Msg::AssignCook(id) => {
+                let callback = self.link.callback(Msg::Joined);
+                let request = Request::put(ListApi::Cook(id).to_string())
+                    .header("Authorization", format!("Bearer {}", &self.token))
                     .body(Nothing)
                     .unwrap();
-                self.fetch_task = FetchService::fetch(request, callback).ok();
+                self.join_task = FetchService::fetch(request, callback).ok();
+            }
+            Msg::Answer(id, rsvp) => {
+                let callback = self.link.callback(Msg::Joined);
+                let body = AttendanceRequest { rsvp };
+                let request = Request::put(ListApi::User(id).to_string())
+                    .header("Authorization", format!("Bearer {}", &self.token))
+                    .header("content-type", "application/json")
+                    .body(Json(&body))
+                    .unwrap();
+                self.join_task = FetchService::fetch(request, callback).ok();
+            }
+            Msg::Joined(res) => {
+                self.join_task = None;
+                if res.status().is_success() {
+                    self.fetch_list();
+                } else {
+                    error!("Error while updating list");
+                }
+            }
+            Msg::Tick => {
+                self.now = Utc::now();
+                return self.list.as_ref().is_some_and(|l| l.deadline.is_some());
+            }
+            Msg::UpdateToken(token) => {
+                self.token = token;
+                self.fetch_list();
             }
         }
         false
@@ -85,16 +199,30 @@ impl Component for ListComponent {
 
     fn view(&self) -> Html {
         if let Some(list) = &self.list {
+            let id = list.id;
             html! {
                 <>
                 <div class="list-header">
                     <h1>{ &list.list_type }</h1>
                     <h2>{ &list.date.format("%A, %-d %B, %C%y") }</h2>
-                    <div class="attendance-content">{ list.users.len() }</div>
+                    { self.view_details(list) }
+                    <div class="attendance-content">{ headcount(list) }</div>
+                    { self.view_deadline(list.deadline) }
+                    { list.diet.as_ref().map_or(html! {}, |diet| html! {
+                        <div class="diet">{ &diet.summary }</div>
+                    }) }
+                    { self.view_answer_button(id, "yes", "Join", list.deadline) }
+                    { self.view_answer_button(id, "maybe", "Maybe", list.deadline) }
+                    { self.view_answer_button(id, "no", "Decline", list.deadline) }
                 </div>
                 <div class="list-container">
-                    { self.view_user_list(&list.users) }
+                    { self.view_user_list(list) }
                 </div>
+                { self.view_waitlist(list) }
+                { self.view_names("Maybe", &list.maybe) }
+                { self.view_names("Not coming", &list.no) }
+                { self.view_names("Not answered yet", &self.unanswered) }
+                { self.view_shopping_list() }
                 </>
             }
         } else {
@@ -108,8 +236,128 @@ impl Component for ListComponent {
 }
 
 impl ListComponent {
-    fn view_user_list(&self, users: &[String]) -> Html {
-        if users.is_empty() {
+    fn fetch_list(&mut self) {
+        let callback = self.link.callback(|res| Msg::Fetch(Box::new(res)));
+        let request = Request::get(ListApi::Get(self.props.id).to_string())
+            .header("Authorization", format!("Bearer {}", &self.token))
+            .body(Nothing)
+            .unwrap();
+        self.fetch_task = FetchService::fetch(request, callback).ok();
+
+        let callback = self.link.callback(Msg::FetchUnanswered);
+        let request = Request::get(ListApi::Unanswered(self.props.id).to_string())
+            .header("Authorization", format!("Bearer {}", &self.token))
+            .body(Nothing)
+            .unwrap();
+        self.unanswered_task = FetchService::fetch(request, callback).ok();
+
+        let callback = self.link.callback(Msg::FetchCook);
+        let request = Request::get(ListApi::Cook(self.props.id).to_string())
+            .header("Authorization", format!("Bearer {}", &self.token))
+            .body(Nothing)
+            .unwrap();
+        self.cook_task = FetchService::fetch(request, callback).ok();
+    }
+
+    fn fetch_shopping_list(&mut self) {
+        let callback = self.link.callback(Msg::FetchShopping);
+        let request = Request::get(ListApi::Shopping(self.props.id).to_string())
+            .header("Authorization", format!("Bearer {}", &self.token))
+            .body(Nothing)
+            .unwrap();
+        self.shopping_task = FetchService::fetch(request, callback).ok();
+    }
+
+    fn view_answer_button(
+        &self,
+        id: usize,
+        rsvp: &'static str,
+        label: &str,
+        deadline: Option<DateTime<Utc>>,
+    ) -> Html {
+        html! {
+            <button
+                class="join-button"
+                disabled=self.is_closed(deadline) || self.join_task.is_some()
+                onclick=self.link.callback(move |_| Msg::Answer(id, rsvp))>
+                { label }
+            </button>
+        }
+    }
+
+    fn is_closed(&self, deadline: Option<DateTime<Utc>>) -> bool {
+        deadline.is_some_and(|deadline| self.now >= deadline)
+    }
+
+    fn view_details(&self, list: &ListResponse) -> Html {
+        html! {
+            <div class="list-details">
+                { list.title.as_ref().map_or(html! {}, |title| html! {
+                    <h3 class="list-title">{ title }</h3>
+                }) }
+                { self.view_cook(list) }
+                { list.menu.as_ref().map_or(html! {}, |menu| html! {
+                    <div class="list-menu">{ menu }</div>
+                }) }
+            </div>
+        }
+    }
+
+    fn view_cook(&self, list: &ListResponse) -> Html {
+        let id = list.id;
+        match (&list.cook, &self.suggested_cook) {
+            (Some(cook), _) => html! {
+                <div class="list-cook">{ format!("Cooked by {}", cook) }</div>
+            },
+            (None, Some(suggestion)) => html! {
+                <div class="list-cook">
+                    { format!("Suggested cook: {}", suggestion) }
+                    <button
+                        class="join-button"
+                        disabled=self.join_task.is_some()
+                        onclick=self.link.callback(move |_| Msg::AssignCook(id))>
+                        { "Assign" }
+                    </button>
+                </div>
+            },
+            (None, None) => html! {},
+        }
+    }
+
+    fn view_shopping_list(&self) -> Html {
+        let shopping = match &self.shopping {
+            Some(shopping) => shopping,
+            None => return html! {},
+        };
+        html! {
+            <div class="list-container">
+                <h3>{ format!("Shopping list: {} for {}", shopping.recipe, shopping.headcount) }</h3>
+                <ul class="user-list">
+                    { for shopping.items.iter().map(|item| html! {
+                        <li class="user-item">{ format_ingredient(item) }</li>
+                    }) }
+                </ul>
+            </div>
+        }
+    }
+
+    fn view_deadline(&self, deadline: Option<DateTime<Utc>>) -> Html {
+        match deadline {
+            Some(deadline) if self.now < deadline => {
+                let left = (deadline - self.now).num_seconds();
+                html! {
+                    <div class="deadline">
+                        { format!("closes in {}:{:02}:{:02}", left / 3600, left / 60 % 60, left % 60) }
+                    </div>
+                }
+            }
+            Some(_) => html! { <div class="deadline">{ "closed" }</div> },
+            None => html! {},
+        }
+    }
+
+    fn view_user_list(&self, list: &ListResponse) -> Html {
+        if list.users.is_empty() {
             html! {
                 <div class="empty-message">
                     { "It seems that no one has signed up yet" }
@@ -118,17 +366,88 @@ impl ListComponent {
         } else {
             html! {
                 <ul class="user-list">
-                    { for users.iter().map(|u| self.view_user(u)) }
+                    { for list.users.iter().map(|u| self.view_user(list, u)) }
                 </ul>
             }
         }
     }
 
-    fn view_user(&self, name: &str) -> Html {
+    fn view_waitlist(&self, list: &ListResponse) -> Html {
+        if list.waitlist.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="list-container">
+                    <h3>{ "Waitlist" }</h3>
+                    <ol class="user-list">
+                        { for list.waitlist.iter().map(|u| self.view_user(list, u)) }
+                    </ol>
+                </div>
+            }
+        }
+    }
+
+    fn view_names(&self, title: &str, names: &[String]) -> Html {
+        if names.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="list-container">
+                    <h3>{ title }</h3>
+                    <ul class="user-list">
+                        { for names.iter().map(|name| html! { <li class="user-item">{ name }</li> }) }
+                    </ul>
+                </div>
+            }
+        }
+    }
+
+    fn view_user(&self, list: &ListResponse, name: &str) -> Html {
         html! {
             <li class="user-item">
                 { name }
+                { list.notes.get(name).map_or(html! {}, |note| html! {
+                    <span class="user-note">{ note }</span>
+                }) }
+                { list.guests.get(name).map_or(html! {}, |guests| self.view_guests(guests)) }
             </li>
         }
     }
+
+    fn view_guests(&self, guests: &Guests) -> Html {
+        let unnamed = guests.count.saturating_sub(guests.names.len());
+        html! {
+            <ul class="guest-list">
+                { for guests.names.iter().map(|name| html! { <li class="guest-item">{ name }</li> }) }
+                { if unnamed > 0 {
+                    html! { <li class="guest-item">{ format!("+{}", unnamed) }</li> }
+                } else {
+                    html! {}
+                } }
+            </ul>
+        }
+    }
+}
+
+/// Returns the number of attendees including their guests.
+fn headcount(list: &ListResponse) -> usize {
+    let guests: usize = list
+        .users
+        .iter()
+        .filter_map(|user| list.guests.get(user))
+        .map(|guests| guests.count)
+        .sum();
+    list.users.len() + guests
+}
+
+/// Formats an ingredient with at most two decimals, e.g. `320 g rice` or
+/// `1.5 onion`.
+fn format_ingredient(ingredient: &Ingredient) -> String {
+    let quantity = format!("{:.2}", ingredient.quantity);
+    let quantity = quantity.trim_end_matches('0').trim_end_matches('.');
+    if ingredient.unit.is_empty() {
+        format!("{} {}", quantity, ingredient.name)
+    } else {
+        format!("{} {} {}", quantity, ingredient.unit, ingredient.name)
+    }
 }
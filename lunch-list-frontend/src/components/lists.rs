@@ -1,3 +1,4 @@
+use chrono::{Datelike, Duration, Local};
 use log::{error, info};
 use yew::{
     agent::{Bridge, Bridged},
@@ -9,16 +10,26 @@ use yew::{
 use yew_router::components::RouterAnchor;
 
 use crate::{
-    api::{ListApi, Response},
-    models::{List, ListsResponse},
+    api::{CalendarApi, HolidayApi, ListApi, Response},
+    models::{
+        CalendarResponse, Holiday, HolidaysResponse, List, ListType, ListTypesResponse,
+        ListsResponse,
+    },
     routes::AppRoute,
     TokenAgent, TokenRequest,
 };
 
 pub struct ListsComponent {
     lists: Vec<List>,
+    types: Vec<ListType>,
+    holidays: Vec<Holiday>,
+    calendar_url: Option<String>,
+    this_week: bool,
     link: ComponentLink<Self>,
     fetch_task: Option<FetchTask>,
+    types_task: Option<FetchTask>,
+    holidays_task: Option<FetchTask>,
+    calendar_task: Option<FetchTask>,
     token: String,
     #[allow(dead_code)]
     token_agent: Box<dyn Bridge<TokenAgent>>,
@@ -26,6 +37,10 @@ pub struct ListsComponent {
 
 pub enum Msg {
     Fetch(Response<ListsResponse>),
+    FetchTypes(Response<ListTypesResponse>),
+    FetchHolidays(Response<HolidaysResponse>),
+    FetchCalendar(Response<CalendarResponse>),
+    ToggleWeek,
     UpdateToken(String),
 }
 
@@ -38,8 +53,15 @@ impl Component for ListsComponent {
         token_agent.send(TokenRequest::GetToken);
         Self {
             lists: Default::default(),
+            types: Default::default(),
+            holidays: Default::default(),
+            calendar_url: Default::default(),
+            this_week: false,
             link,
             fetch_task: Default::default(),
+            types_task: Default::default(),
+            holidays_task: Default::default(),
+            calendar_task: Default::default(),
             token: "".to_string(),
             token_agent,
         }
@@ -63,14 +85,80 @@ impl Component for ListsComponent {
                     error!("Error while fetching lists");
                 }
             }
+            Msg::FetchTypes(res) => {
+                self.types_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(data) => {
+                            self.types = data.types;
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching list types: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching list types");
+                }
+            }
+            Msg::FetchHolidays(res) => {
+                self.holidays_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(data) => {
+                            self.holidays = data.holidays;
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching holidays: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching holidays");
+                }
+            }
+            Msg::FetchCalendar(res) => {
+                self.calendar_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                if meta.status.is_success() {
+                    match data {
+                        Ok(data) => {
+                            self.calendar_url = Some(data.url);
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching calendar url: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching calendar url");
+                }
+            }
+            Msg::ToggleWeek => {
+                self.this_week = !self.this_week;
+                self.fetch_lists();
+                return true;
+            }
             Msg::UpdateToken(token) => {
                 self.token = token;
-                let callback = self.link.callback(Msg::Fetch);
-                let request = Request::get(ListApi::GetAll.to_string())
+                self.fetch_lists();
+
+                let callback = self.link.callback(Msg::FetchTypes);
+                let request = Request::get(ListApi::Types.to_string())
+                    .header("Authorization", format!("Bearer {}", &self.token))
+                    .body(Nothing)
+                    .unwrap();
+                self.types_task = FetchService::fetch(request, callback).ok();
+
+                let callback = self.link.callback(Msg::FetchHolidays);
+                let request = Request::get(HolidayApi::GetAll.to_string())
                     .header("Authorization", format!("Bearer {}", &self.token))
                     .body(Nothing)
                     .unwrap();
-                self.fetch_task = FetchService::fetch(request, callback).ok();
+                self.holidays_task = FetchService::fetch(request, callback).ok();
+
+                let callback = self.link.callback(Msg::FetchCalendar);
+                let request = Request::get(CalendarApi::Url.to_string())
+                    .header("Authorization", format!("Bearer {}", &self.token))
+                    .body(Nothing)
+                    .unwrap();
+                self.calendar_task = FetchService::fetch(request, callback).ok();
             }
         }
         false
@@ -85,6 +173,15 @@ impl Component for ListsComponent {
             <>
             <div class="list-header">
                 <h1>{ "lunch-list" }</h1>
+                <a class="calendar-link" onclick=self.link.callback(|_| Msg::ToggleWeek)>
+                    { if self.this_week { "All lists" } else { "This week" } }
+                </a>
+                <RouterAnchor<AppRoute> classes="calendar-link" route=AppRoute::Stats>
+                    { "Statistics" }
+                </RouterAnchor<AppRoute>>
+                { self.calendar_url.as_ref().map_or(html! {}, |url| html! {
+                    <a class="calendar-link" href=url.clone()>{ "Subscribe in your calendar" }</a>
+                }) }
             </div>
             <div class="list-container">
                 <ul class="list-list">
@@ -97,20 +194,46 @@ impl Component for ListsComponent {
 }
 
 impl ListsComponent {
-    fn view_list(&self, list: &List) -> Html {
-        let class = match list.list_type.as_str() {
-            "lunch" => "lunch-item",
-            "dinner" => "dinner-item",
-            _ => "",
+    /// Fetches the latest lists, or only the lists of the current week.
+    fn fetch_lists(&mut self) {
+        let api = if self.this_week {
+            let today = Local::now().naive_local().date();
+            let monday = today - Duration::days(today.weekday().num_days_from_monday().into());
+            ListApi::Between(monday, monday + Duration::days(6))
+        } else {
+            ListApi::GetAll
         };
+        let callback = self.link.callback(Msg::Fetch);
+        let request = Request::get(api.to_string())
+            .header("Authorization", format!("Bearer {}", &self.token))
+            .body(Nothing)
+            .unwrap();
+        self.fetch_task = FetchService::fetch(request, callback).ok();
+    }
+
+    fn view_list(&self, list: &List) -> Html {
+        let class = format!("{}-item", list.list_type);
+        let holiday = self.holidays.iter().find(|h| h.date == list.date);
+        let list_type = self.types.iter().find(|t| t.list_type == list.list_type);
+        let title = list_type.map_or(list.list_type.as_str(), |t| t.name.as_str());
+        let style = list_type
+            .map(|t| format!("border-left: 4px solid {}", t.color))
+            .unwrap_or_default();
+        let counts = format!(
+            "{} yes, {} maybe, {} no",
+            list.counts.yes, list.counts.maybe, list.counts.no
+        );
         html! {
             <RouterAnchor<AppRoute> classes="list-anchor" route=AppRoute::List { id: list.id }>
-                <li class=("list-item", class)>
+                <li class=("list-item", class.as_str(), holiday.map(|_| "closed-item")) style=style title=title>
                     <div class="date-content">
                         { &list.date.format("%A, %-d %B") }
+                        { holiday.map_or(html! {}, |holiday| html! {
+                            <span class="holiday-name">{ &holiday.name }</span>
+                        }) }
                     </div>
-                    <div class="attendance-content">
-                        { &list.size }
+                    <div class="attendance-content" title=counts>
+                        { list.counts.yes }
                     </div>
                 </li>
             </RouterAnchor<AppRoute>>
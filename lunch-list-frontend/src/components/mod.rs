@@ -2,8 +2,10 @@ mod app;
 mod list;
 mod lists;
 mod login;
+mod stats;
 
 pub use app::App;
 pub use list::ListComponent;
 pub use lists::ListsComponent;
 pub use login::LoginComponent;
+pub use stats::StatsComponent;
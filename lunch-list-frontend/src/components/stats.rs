@@ -0,0 +1,169 @@
+use log::{error, info};
+use yew::{
+    agent::{Bridge, Bridged},
+    format::{Json, Nothing},
+    html,
+    services::fetch::{FetchService, FetchTask, Request},
+    Component, ComponentLink, Html, ShouldRender,
+};
+
+use crate::{
+    api::{Response, StatsApi},
+    models::StatsResponse,
+    TokenAgent, TokenRequest,
+};
+
+pub struct StatsComponent {
+    stats: Option<StatsResponse>,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+    token: String,
+    #[allow(dead_code)]
+    token_agent: Box<dyn Bridge<TokenAgent>>,
+}
+
+pub enum Msg {
+    Fetch(Response<StatsResponse>),
+    UpdateToken(String),
+}
+
+impl Component for StatsComponent {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut token_agent = TokenAgent::bridge(link.callback(Msg::UpdateToken));
+        token_agent.send(TokenRequest::GetToken);
+        Self {
+            stats: Default::default(),
+            link,
+            fetch_task: Default::default(),
+            token: Default::default(),
+            token_agent,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Fetch(res) => {
+                self.fetch_task = None;
+                let (meta, Json(data)) = res.into_parts();
+                info!("META: {:?}, {:?}", meta, data);
+                if meta.status.is_success() {
+                    match data {
+                        Ok(stats) => {
+                            self.stats = Some(stats);
+                            return true;
+                        }
+                        Err(e) => error!("Error when fetching stats: {}", e),
+                    }
+                } else {
+                    error!("Error while fetching stats");
+                }
+            }
+            Msg::UpdateToken(token) => {
+                self.token = token;
+                let callback = self.link.callback(Msg::Fetch);
+                let request = Request::get(StatsApi::Get.to_string())
+                    .header("Authorization", format!("Bearer {}", &self.token))
+                    .body(Nothing)
+                    .unwrap();
+                self.fetch_task = FetchService::fetch(request, callback).ok();
+            }
+        }
+        false
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => {
+                return html! {
+                    <div class="list-header">
+                        <h1>{ "loading..." }</h1>
+                    </div>
+                }
+            }
+        };
+        let weekdays: Vec<_> = stats
+            .weekdays
+            .iter()
+            .map(|w| (w.weekday.clone(), w.average))
+            .collect();
+        let types: Vec<_> = stats
+            .types
+            .iter()
+            .map(|t| (t.list_type.clone(), t.average))
+            .collect();
+        html! {
+            <>
+            <div class="list-header">
+                <h1>{ "statistics" }</h1>
+                <h2>{ "Last 90 days" }</h2>
+            </div>
+            { view_averages("Average per weekday", &weekdays) }
+            { view_averages("Average per type", &types) }
+            <div class="list-container">
+                <h3>{ "Top attendees" }</h3>
+                <ul class="user-list">
+                    { for stats.top_attendees.iter().map(|user| html! {
+                        <li class="user-item">{ format!("{} ({})", user.username, user.lists) }</li>
+                    }) }
+                </ul>
+            </div>
+            { view_daily(stats) }
+            </>
+        }
+    }
+}
+
+/// Shows averages as horizontal bars relative to the highest average.
+fn view_averages(title: &str, averages: &[(String, f64)]) -> Html {
+    let max = averages.iter().map(|(_, a)| *a).fold(0.0, f64::max);
+    html! {
+        <div class="list-container">
+            <h3>{ title }</h3>
+            <ul class="user-list">
+                { for averages.iter().map(|(label, average)| html! {
+                    <li class="user-item stat-item">
+                        <span class="stat-label">{ label }</span>
+                        <div class="stat-track">
+                            <div class="stat-bar" style=format!("width: {:.0}%", percentage(*average, max))></div>
+                        </div>
+                        { format!("{:.1}", average) }
+                    </li>
+                }) }
+            </ul>
+        </div>
+    }
+}
+
+fn view_daily(stats: &StatsResponse) -> Html {
+    let max = stats.daily.iter().map(|d| d.headcount).max().unwrap_or(0) as f64;
+    html! {
+        <div class="list-container">
+            <h3>{ "Daily headcount" }</h3>
+            <div class="daily-chart">
+                { for stats.daily.iter().map(|day| html! {
+                    <div
+                        class="daily-bar"
+                        title=format!("{}: {}", day.date.format("%-d %B"), day.headcount)
+                        style=format!("height: {:.0}%", percentage(day.headcount as f64, max))>
+                    </div>
+                }) }
+            </div>
+        </div>
+    }
+}
+
+fn percentage(value: f64, max: f64) -> f64 {
+    if max > 0.0 {
+        value / max * 100.0
+    } else {
+        0.0
+    }
+}
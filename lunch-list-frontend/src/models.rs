@@ -1,4 +1,6 @@
-use chrono::NaiveDate;
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
@@ -23,7 +25,36 @@ pub struct ListResponse {
     pub date: NaiveDate,
     #[serde(rename = "type")]
     pub list_type: String,
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+    pub title: Option<String>,
+    pub menu: Option<String>,
+    pub cook: Option<String>,
+    pub recipe: Option<usize>,
     pub users: Vec<String>,
+    #[serde(default)]
+    pub waitlist: Vec<String>,
+    #[serde(default)]
+    pub guests: HashMap<String, Guests>,
+    #[serde(default)]
+    pub notes: HashMap<String, String>,
+    #[serde(default)]
+    pub maybe: Vec<String>,
+    #[serde(default)]
+    pub no: Vec<String>,
+    pub diet: Option<DietSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DietSummary {
+    pub summary: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Guests {
+    pub count: usize,
+    #[serde(default)]
+    pub names: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,5 +63,103 @@ pub struct List {
     pub date: NaiveDate,
     #[serde(rename = "type")]
     pub list_type: String,
-    pub size: usize,
+    pub counts: RsvpCounts,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RsvpCounts {
+    pub yes: usize,
+    pub maybe: usize,
+    pub no: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceRequest {
+    pub rsvp: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CookResponse {
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShoppingResponse {
+    pub recipe: String,
+    pub headcount: usize,
+    pub items: Vec<Ingredient>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Ingredient {
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsResponse {
+    pub weekdays: Vec<WeekdayAverage>,
+    pub types: Vec<TypeAverage>,
+    pub top_attendees: Vec<UserTotal>,
+    pub daily: Vec<DailyHeadcount>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeekdayAverage {
+    pub weekday: String,
+    pub average: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TypeAverage {
+    #[serde(rename = "type")]
+    pub list_type: String,
+    pub average: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserTotal {
+    pub username: String,
+    pub lists: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyHeadcount {
+    pub date: NaiveDate,
+    pub headcount: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarResponse {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnansweredResponse {
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTypesResponse {
+    pub types: Vec<ListType>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListType {
+    #[serde(rename = "type")]
+    pub list_type: String,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HolidaysResponse {
+    pub holidays: Vec<Holiday>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
 }
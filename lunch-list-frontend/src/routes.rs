@@ -8,6 +8,8 @@ pub enum AppRoute {
     Dashboard,
     #[to = "/list/{id}"]
     List { id: usize },
+    #[to = "/stats!"]
+    Stats,
     #[to = "/user!"]
     User,
     #[to = "/page-not-found"]